@@ -0,0 +1,141 @@
+/*!
+This module contains the [EPWBundle] struct, which loads an EPW file together with the `.ddy` and
+`.stat` files that are distributed with it.
+
+Weather file downloads ship as a set of files sharing the same name, e.g.
+`USA_FL_Tampa.Intl.AP.722110_TMY3.epw`, `USA_FL_Tampa.Intl.AP.722110_TMY3.ddy` and
+`USA_FL_Tampa.Intl.AP.722110_TMY3.stat`. The EPW file is required, the companion files are loaded
+when they are present.
+
+*/
+use crate::ddy::DDYFile;
+use crate::error::EPWParseError;
+use crate::stat::STATFile;
+use crate::EPWFile;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An EPW file with its companion design day and statistics files
+#[derive(Debug)]
+pub struct EPWBundle {
+    pub epw: EPWFile,
+    pub ddy: Option<DDYFile>,
+    pub stat: Option<STATFile>,
+}
+
+impl EPWBundle {
+    /// Load the files for a station from a directory
+    ///
+    /// The directory must contain exactly one `.epw` file, the `.ddy` and `.stat` files with the
+    /// same name are loaded if they exist.
+    ///
+    /// ## Parameters
+    /// - `path`: Path to the directory on the filesystem
+    ///
+    /// ## Returns
+    /// An initialized EPWBundle or an EPWParseError
+    pub fn from_dir(path: &str) -> Result<Self, EPWParseError> {
+        let entries = match fs::read_dir(path) {
+            Ok(val) => val,
+            Err(e) => return Err(EPWParseError::FileNotFound(e.to_string())),
+        };
+
+        let mut epw_paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| _has_extension(path, "epw"))
+            .collect();
+
+        match epw_paths.len() {
+            0 => Err(EPWParseError::FileNotFound(format!(
+                "No EPW file found in {}",
+                path
+            ))),
+            1 => Self::from_path(&epw_paths.remove(0).to_string_lossy()),
+            _ => Err(EPWParseError::UnexpectedData(format!(
+                "Multiple EPW files found in {}",
+                path
+            ))),
+        }
+    }
+
+    /// Load an EPW file and the `.ddy` and `.stat` files next to it with the same name
+    ///
+    /// ## Parameters
+    /// - `path`: Path to the `.epw` file on the filesystem
+    ///
+    /// ## Returns
+    /// An initialized EPWBundle or an EPWParseError
+    pub fn from_path(path: &str) -> Result<Self, EPWParseError> {
+        let epw = EPWFile::from_path(path)?;
+
+        let ddy_path = Path::new(path).with_extension("ddy");
+        let ddy = match ddy_path.is_file() {
+            true => Some(DDYFile::from_path(&ddy_path.to_string_lossy())?),
+            false => None,
+        };
+
+        let stat_path = Path::new(path).with_extension("stat");
+        let stat = match stat_path.is_file() {
+            true => Some(STATFile::from_path(&stat_path.to_string_lossy())?),
+            false => None,
+        };
+
+        Ok(Self { epw, ddy, stat })
+    }
+}
+
+fn _has_extension(path: &Path, extension: &str) -> bool {
+    match path.extension() {
+        Some(val) => val.eq_ignore_ascii_case(extension),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "./data/USA_FL_Tampa_TMY2.epw";
+
+    const TEST_STAT: &str = " - Monthly Statistics for Dry Bulb temperatures
+                \tJan\tFeb\tMar\tApr\tMay\tJun\tJul\tAug\tSep\tOct\tNov\tDec
+ Maximum        \t 27.2\t 28.9\t 29.4\t 31.1\t 33.3\t 34.4\t 35.0\t 34.4\t 33.9\t 32.2\t 30.0\t 28.3
+";
+
+    #[test]
+    fn test_load_bundle() {
+        // The data directory has the EPW file without its companion files
+        let bundle = EPWBundle::from_dir("./data").unwrap();
+        assert_eq!("TAMPA", bundle.epw.header.location.city);
+        assert!(bundle.ddy.is_none());
+        assert!(bundle.stat.is_none());
+
+        let dir = std::env::temp_dir().join("epw_rs_test_bundle");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir_path = dir.to_string_lossy().to_string();
+        assert!(EPWBundle::from_dir(&dir_path).is_err());
+
+        let epw_path = dir.join("Tampa.epw");
+        fs::copy(TEST_FILE, &epw_path).unwrap();
+        let ddy = DDYFile::from_header(&bundle.epw.header).unwrap();
+        let mut ddy_file = fs::File::create(dir.join("Tampa.ddy")).unwrap();
+        ddy.to_idf(&mut ddy_file).unwrap();
+        fs::write(dir.join("Tampa.stat"), TEST_STAT).unwrap();
+
+        let bundle = EPWBundle::from_dir(&dir_path).unwrap();
+        assert_eq!(8760, bundle.epw.data.len());
+        assert_eq!(ddy.design_days.len(), bundle.ddy.unwrap().design_days.len());
+        assert_eq!(1, bundle.stat.unwrap().monthly_tables.len());
+
+        fs::remove_file(dir.join("Tampa.stat")).unwrap();
+        let bundle = EPWBundle::from_path(&epw_path.to_string_lossy()).unwrap();
+        assert!(bundle.ddy.is_some());
+        assert!(bundle.stat.is_none());
+
+        fs::copy(TEST_FILE, dir.join("Other.epw")).unwrap();
+        assert!(EPWBundle::from_dir(&dir_path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
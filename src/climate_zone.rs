@@ -0,0 +1,237 @@
+/*!
+ASHRAE Standard 169-2006 climate zone classification.
+
+The thermal zone, 1 (very hot) to 8 (subarctic), is set by the heating degree days at 18°C (HDD18)
+and the cooling degree days at 10°C (CDD10), computed from the daily mean dry bulb temperatures:
+
+| Zone       | Criteria                           |
+|------------|------------------------------------|
+| 1          | 5000 < CDD10                       |
+| 2          | 3500 < CDD10 ≤ 5000                |
+| 3A, 3B     | 2500 < CDD10 ≤ 3500                |
+| 4A, 4B     | CDD10 ≤ 2500 and HDD18 ≤ 3000      |
+| 3C         | HDD18 ≤ 2000                       |
+| 4C         | 2000 < HDD18 ≤ 3000                |
+| 5A, 5B, 5C | 3000 < HDD18 ≤ 4000                |
+| 6A, 6B     | 4000 < HDD18 ≤ 5000                |
+| 7          | 5000 < HDD18 ≤ 7000                |
+| 8          | 7000 < HDD18                       |
+
+The marine zones 3C, 4C and 5C use only the heating degree days. Zones 1 to 6 are further divided
+by moisture regime:
+- C, marine, zones 3 to 5 only: the coldest month mean is between -3°C and 18°C, the warmest month
+  mean is below 22°C, at least four months have a mean above 10°C, and the wettest month of the
+  cold season has at least three times the precipitation of the driest month of the warm season.
+  The cold season is October to March in the northern hemisphere and April to September in the
+  southern one.
+- B, dry: not marine, and the annual precipitation in cm is below `2 (T + 7)`, where `T` is the
+  annual mean temperature in °C
+- A, moist: otherwise
+
+The moisture regime needs the liquid precipitation depth, which many files don't have. It is
+`None` when the file has no precipitation values.
+
+*/
+use crate::degree_days::DegreeDayMethod;
+use crate::summary::{monthly_means, monthly_totals, Statistics};
+use crate::EPWFile;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MoistureRegime {
+    Moist,
+    Dry,
+    Marine,
+}
+
+impl MoistureRegime {
+    /// Letter of the moisture regime in the climate zone code
+    pub fn letter(&self) -> char {
+        match self {
+            MoistureRegime::Moist => 'A',
+            MoistureRegime::Dry => 'B',
+            MoistureRegime::Marine => 'C',
+        }
+    }
+}
+
+/// ASHRAE climate zone with the values it was derived from
+#[derive(Debug)]
+pub struct ClimateZone {
+    /// Thermal zone, 1 to 8
+    pub thermal_zone: u8,
+    /// Moisture regime, `None` for zones 7 and 8 and for files without precipitation values
+    pub moisture_regime: Option<MoistureRegime>,
+    /// Annual heating degree days at 18°C in °C·days
+    pub heating_degree_days_18: f64,
+    /// Annual cooling degree days at 10°C in °C·days
+    pub cooling_degree_days_10: f64,
+    /// Annual mean dry bulb temperature in °C
+    pub annual_mean_temperature: f64,
+    /// Mean dry bulb temperature of each month in °C, January first
+    pub monthly_mean_temperature: Vec<f64>,
+    /// Total precipitation of each month in mm, January first, NaN without precipitation values
+    pub monthly_precipitation: Vec<f64>,
+    /// Annual precipitation in mm, NaN without precipitation values
+    pub annual_precipitation: f64,
+}
+
+impl ClimateZone {
+    /// Climate zone code, e.g. `2A` or `7`
+    pub fn code(&self) -> String {
+        match self.moisture_regime {
+            Some(regime) => format!("{}{}", self.thermal_zone, regime.letter()),
+            None => self.thermal_zone.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ClimateZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl EPWFile {
+    /// Classify the climate of the file in an ASHRAE Standard 169-2006 climate zone
+    ///
+    /// ## Returns
+    /// The climate zone and the values it was derived from
+    pub fn ashrae_climate_zone(&self) -> ClimateZone {
+        let data = &self.data;
+        let degree_days = data.degree_days(18., 10., DegreeDayMethod::DailyMean);
+        let heating = degree_days.annual_heating;
+        let cooling = degree_days.annual_cooling;
+
+        let monthly_mean_temperature = monthly_means(&data.timestamp, &data.dry_bulb_temperature);
+        let has_precipitation = data.liquid_precipitation_depth.iter().any(|p| !p.is_nan());
+        let monthly_precipitation = match has_precipitation {
+            true => monthly_totals(&data.timestamp, &data.liquid_precipitation_depth),
+            false => vec![f64::NAN; 12],
+        };
+        let annual_mean_temperature = Statistics::from_values(&data.dry_bulb_temperature).mean;
+        let annual_precipitation: f64 = monthly_precipitation.iter().sum();
+
+        let marine = has_precipitation
+            && heating <= 4000.
+            && _is_marine(
+                &monthly_mean_temperature,
+                &monthly_precipitation,
+                self.header.location.latitude >= 0.,
+            );
+        let thermal_zone = _thermal_zone(heating, cooling, marine);
+        let dry = annual_precipitation / 10. < 2. * (annual_mean_temperature + 7.);
+        let moisture_regime = match (marine, dry) {
+            _ if thermal_zone > 6 || !has_precipitation => None,
+            (true, _) => Some(MoistureRegime::Marine),
+            (false, true) => Some(MoistureRegime::Dry),
+            (false, false) => Some(MoistureRegime::Moist),
+        };
+
+        ClimateZone {
+            thermal_zone,
+            moisture_regime,
+            heating_degree_days_18: heating,
+            cooling_degree_days_10: cooling,
+            annual_mean_temperature,
+            monthly_mean_temperature,
+            monthly_precipitation,
+            annual_precipitation,
+        }
+    }
+}
+
+/// Thermal zone from the ASHRAE 169-2006 criteria, marine zones use only the heating degree days
+fn _thermal_zone(heating: f64, cooling: f64, marine: bool) -> u8 {
+    match (heating, cooling) {
+        (h, _) if marine && h <= 2000. => 3,
+        (h, _) if marine && h <= 3000. => 4,
+        (_, c) if !marine && c > 5000. => 1,
+        (_, c) if !marine && c > 3500. => 2,
+        (_, c) if !marine && c > 2500. => 3,
+        (h, _) if !marine && h <= 3000. => 4,
+        (h, _) if h <= 4000. => 5,
+        (h, _) if h <= 5000. => 6,
+        (h, _) if h <= 7000. => 7,
+        _ => 8,
+    }
+}
+
+/// Whether the monthly temperatures and precipitation meet the marine (C) criteria
+fn _is_marine(
+    monthly_temperature: &[f64],
+    monthly_precipitation: &[f64],
+    northern_hemisphere: bool,
+) -> bool {
+    let temperatures: Vec<f64> = monthly_temperature
+        .iter()
+        .copied()
+        .filter(|t| !t.is_nan())
+        .collect();
+    let coldest = temperatures.iter().copied().fold(f64::INFINITY, f64::min);
+    let warmest = temperatures
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let warm_months = temperatures.iter().filter(|t| **t > 10.).count();
+
+    // Months 10 to 3 are the cold season in the northern hemisphere
+    let is_cold_season = |idx: usize| (idx >= 9 || idx <= 2) == northern_hemisphere;
+    let wettest_cold_month = (0..12)
+        .filter(|idx| is_cold_season(*idx))
+        .map(|idx| monthly_precipitation[idx])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let driest_warm_month = (0..12)
+        .filter(|idx| !is_cold_season(*idx))
+        .map(|idx| monthly_precipitation[idx])
+        .fold(f64::INFINITY, f64::min);
+
+    (-3. ..=18.).contains(&coldest)
+        && warmest < 22.
+        && warm_months >= 4
+        && wettest_cold_month >= 3. * driest_warm_month
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ashrae_climate_zone() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let zone = epw.ashrae_climate_zone();
+        assert_eq!(2, zone.thermal_zone);
+        assert!(zone.cooling_degree_days_10 > 3500. && zone.cooling_degree_days_10 <= 5000.);
+        assert_eq!(None, zone.moisture_regime);
+        assert_eq!("2", zone.code());
+
+        // About 1300 mm of rain a year
+        epw.data.liquid_precipitation_depth = vec![0.15; 8760];
+        assert_eq!("2A", epw.ashrae_climate_zone().to_string());
+
+        // About 90 mm of rain a year
+        epw.data.liquid_precipitation_depth = vec![0.01; 8760];
+        let zone = epw.ashrae_climate_zone();
+        assert_eq!(Some(MoistureRegime::Dry), zone.moisture_regime);
+        assert!((zone.annual_precipitation - 87.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_thermal_zone_boundaries() {
+        // Zone 3 is set by the cooling degree days, whatever the heating degree days
+        assert_eq!(3, _thermal_zone(2500., 3000., false));
+        assert_eq!(3, _thermal_zone(3500., 2500.1, false));
+        assert_eq!(2, _thermal_zone(1000., 3500.1, false));
+        assert_eq!(4, _thermal_zone(2500., 2500., false));
+        assert_eq!(4, _thermal_zone(1500., 2000., false));
+        assert_eq!(4, _thermal_zone(3000., 2500., false));
+        assert_eq!(5, _thermal_zone(3000.1, 2500., false));
+        assert_eq!(6, _thermal_zone(4000.1, 1000., false));
+
+        // Marine zones
+        assert_eq!(3, _thermal_zone(2000., 1000., true));
+        assert_eq!(4, _thermal_zone(2000.1, 1000., true));
+        assert_eq!(4, _thermal_zone(3000., 1000., true));
+        assert_eq!(5, _thermal_zone(3000.1, 1000., true));
+    }
+}
@@ -0,0 +1,145 @@
+/*!
+Support for reading compressed EPW files, enabled with the `compression` feature.
+
+Weather files are commonly distributed as zip archives containing the `.epw`, `.ddy` and `.stat`
+files for a station, or stored gzip compressed. The format is detected from the first bytes of the
+file rather than the extension, so renamed files are handled as well.
+
+*/
+use crate::error::EPWParseError;
+use crate::EPWFile;
+use flate2::bufread::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use zip::ZipArchive;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = &[0x50, 0x4b, 0x03, 0x04];
+
+impl EPWFile {
+    /// Create an EPWFile instance from a member of a zip archive
+    ///
+    /// ## Parameters
+    /// - `path`: Path to the zip archive on the filesystem
+    /// - `entry`: Name of the member to read, e.g. `USA_FL_Tampa.Intl.AP.722110_TMY3.epw`
+    ///
+    /// ## Returns
+    /// An initialized EPWFile or an EPWParseError
+    pub fn from_zip_entry(path: &str, entry: &str) -> Result<Self, EPWParseError> {
+        let mut archive = _open_zip(path)?;
+        _read_zip_entry(&mut archive, entry)
+    }
+}
+
+/// Read an EPW file from a file that may be plain text, gzip compressed or a zip archive
+pub(crate) fn from_buffered_file(mut reader: BufReader<File>) -> Result<EPWFile, EPWParseError> {
+    let magic = match reader.fill_buf() {
+        Ok(val) => val,
+        Err(e) => return Err(EPWParseError::FileNotFound(e.to_string())),
+    };
+
+    if magic.starts_with(GZIP_MAGIC) {
+        EPWFile::from_reader(BufReader::new(MultiGzDecoder::new(reader)))
+    } else if magic.starts_with(ZIP_MAGIC) {
+        let mut archive = match ZipArchive::new(reader) {
+            Ok(val) => val,
+            Err(e) => return Err(EPWParseError::Compression(e.to_string())),
+        };
+        let entries: Vec<String> = archive
+            .file_names()
+            .filter(|name| name.to_ascii_lowercase().ends_with(".epw"))
+            .map(String::from)
+            .collect();
+        match entries.len() {
+            0 => Err(EPWParseError::Compression(
+                "No EPW file found in archive".to_string(),
+            )),
+            1 => _read_zip_entry(&mut archive, &entries[0]),
+            _ => Err(EPWParseError::Compression(format!(
+                "Multiple EPW files found in archive: {}",
+                entries.join(", ")
+            ))),
+        }
+    } else {
+        EPWFile::from_reader(reader)
+    }
+}
+
+fn _open_zip(path: &str) -> Result<ZipArchive<BufReader<File>>, EPWParseError> {
+    let f = match File::open(path) {
+        Ok(val) => val,
+        Err(e) => return Err(EPWParseError::FileNotFound(e.to_string())),
+    };
+
+    match ZipArchive::new(BufReader::new(f)) {
+        Ok(val) => Ok(val),
+        Err(e) => Err(EPWParseError::Compression(e.to_string())),
+    }
+}
+
+fn _read_zip_entry(
+    archive: &mut ZipArchive<BufReader<File>>,
+    entry: &str,
+) -> Result<EPWFile, EPWParseError> {
+    let file = match archive.by_name(entry) {
+        Ok(val) => val,
+        Err(e) => {
+            return Err(EPWParseError::Compression(format!(
+                "Unable to read {} from archive [{}]",
+                entry, e
+            )))
+        }
+    };
+    EPWFile::from_reader(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EPWFile;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const TEST_FILE: &str = "./data/USA_FL_Tampa_TMY2.epw";
+
+    #[test]
+    fn test_read_gzip() {
+        let path = std::env::temp_dir().join("epw_rs_test_read_gzip.epw.gz");
+        let mut encoder = GzEncoder::new(fs::File::create(&path).unwrap(), Compression::fast());
+        encoder.write_all(&fs::read(TEST_FILE).unwrap()).unwrap();
+        encoder.finish().unwrap();
+
+        let epw = EPWFile::from_path(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!("TAMPA", epw.header.location.city);
+        assert_eq!(8760, epw.data.timestamp.len());
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let path = std::env::temp_dir().join("epw_rs_test_read_zip.zip");
+        let mut writer = ZipWriter::new(fs::File::create(&path).unwrap());
+        writer
+            .start_file("USA_FL_Tampa_TMY2.stat", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"Statistics").unwrap();
+        writer
+            .start_file("USA_FL_Tampa_TMY2.epw", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&fs::read(TEST_FILE).unwrap()).unwrap();
+        writer.finish().unwrap();
+
+        let path = path.to_str().unwrap();
+        let epw = EPWFile::from_path(path).unwrap();
+        assert_eq!("TAMPA", epw.header.location.city);
+        assert_eq!(8760, epw.data.timestamp.len());
+
+        let epw = EPWFile::from_zip_entry(path, "USA_FL_Tampa_TMY2.epw").unwrap();
+        assert_eq!("TAMPA", epw.header.location.city);
+        assert!(EPWFile::from_zip_entry(path, "missing.epw").is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+/*!
+Physical consistency checks for the humidity fields of [WeatherData].
+
+The dry bulb temperature, dew point temperature and relative humidity are redundant: any two of
+them determine the third. Files assembled from several sources often disagree with themselves, so
+[WeatherData::check_humidity] recomputes the relative humidity from the temperatures for every
+record and reports the records where the two don't match, and [WeatherData::repair_humidity]
+rewrites one of the columns to make them consistent again.
+
+The relative humidity is the ratio of the vapor pressure at the dew point to the saturation
+vapor pressure at the dry bulb temperature, so it does not depend on the atmospheric pressure.
+
+*/
+use crate::psychrometrics::{dew_point_temperature, relative_humidity, saturation_vapor_pressure};
+use crate::WeatherData;
+use chrono::{DateTime, FixedOffset};
+
+/// Default tolerance for [WeatherData::check_humidity], in percentage points of relative humidity
+pub const DEFAULT_RELATIVE_HUMIDITY_TOLERANCE: f64 = 10.;
+
+#[derive(Debug, PartialEq)]
+pub enum HumidityIssue {
+    /// The dew point temperature is above the dry bulb temperature
+    DewPointAboveDryBulb,
+    /// The relative humidity doesn't match the one computed from the temperatures
+    RelativeHumidityMismatch,
+}
+
+/// An inconsistent record found by [WeatherData::check_humidity]
+#[derive(Debug)]
+pub struct HumidityInconsistency {
+    /// Index of the record in the [WeatherData] columns
+    pub index: usize,
+    pub timestamp: DateTime<FixedOffset>,
+    pub issue: HumidityIssue,
+    pub dry_bulb_temperature: f64,
+    pub dew_point_temperature: f64,
+    pub relative_humidity: f64,
+    /// Relative humidity computed from the dry bulb and dew point temperatures
+    pub computed_relative_humidity: f64,
+    /// Size of the inconsistency, in °C for [HumidityIssue::DewPointAboveDryBulb] and in
+    /// percentage points of relative humidity for [HumidityIssue::RelativeHumidityMismatch]
+    pub magnitude: f64,
+}
+
+/// Which column to keep when repairing inconsistent humidity fields
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HumidityRepair {
+    /// Keep the dew point and rewrite the relative humidity. Dew points above the dry bulb
+    /// temperature are lowered to the dry bulb temperature.
+    TrustDewPoint,
+    /// Keep the relative humidity and rewrite the dew point
+    TrustRelativeHumidity,
+}
+
+impl WeatherData {
+    /// Find the records where the humidity fields are physically inconsistent
+    ///
+    /// Records with a missing dry bulb, dew point or relative humidity value are skipped.
+    ///
+    /// ## Parameters
+    /// - `tolerance`: Largest accepted difference between the file and computed relative
+    ///   humidity, in percentage points. See [DEFAULT_RELATIVE_HUMIDITY_TOLERANCE].
+    ///
+    /// ## Returns
+    /// The inconsistent records, in order
+    pub fn check_humidity(&self, tolerance: f64) -> Vec<HumidityInconsistency> {
+        let mut inconsistencies: Vec<HumidityInconsistency> = Vec::new();
+        for idx in 0..self.timestamp.len() {
+            let dry_bulb = self.dry_bulb_temperature[idx];
+            let dew_point = self.dew_point_temperature[idx];
+            let rh = self.relative_humidity[idx];
+            if dry_bulb.is_nan() || dew_point.is_nan() || rh.is_nan() {
+                continue;
+            }
+
+            let computed = relative_humidity(dry_bulb, dew_point);
+            let (issue, magnitude) = match dew_point > dry_bulb {
+                true => (HumidityIssue::DewPointAboveDryBulb, dew_point - dry_bulb),
+                false => (
+                    HumidityIssue::RelativeHumidityMismatch,
+                    (rh - computed).abs(),
+                ),
+            };
+            if issue == HumidityIssue::RelativeHumidityMismatch && magnitude <= tolerance {
+                continue;
+            }
+
+            inconsistencies.push(HumidityInconsistency {
+                index: idx,
+                timestamp: self.timestamp[idx],
+                issue,
+                dry_bulb_temperature: dry_bulb,
+                dew_point_temperature: dew_point,
+                relative_humidity: rh,
+                computed_relative_humidity: computed,
+                magnitude,
+            });
+        }
+        inconsistencies
+    }
+
+    /// Rewrite the humidity fields of the inconsistent records
+    ///
+    /// ## Parameters
+    /// - `strategy`: Which column to keep
+    /// - `tolerance`: Largest accepted difference between the file and computed relative
+    ///   humidity, in percentage points. See [DEFAULT_RELATIVE_HUMIDITY_TOLERANCE].
+    ///
+    /// ## Returns
+    /// The records that were repaired, with the values they had before the repair
+    pub fn repair_humidity(
+        &mut self,
+        strategy: HumidityRepair,
+        tolerance: f64,
+    ) -> Vec<HumidityInconsistency> {
+        let inconsistencies = self.check_humidity(tolerance);
+        for inconsistency in &inconsistencies {
+            let idx = inconsistency.index;
+            let dry_bulb = inconsistency.dry_bulb_temperature;
+            match strategy {
+                HumidityRepair::TrustDewPoint => {
+                    let dew_point = inconsistency.dew_point_temperature.min(dry_bulb);
+                    self.dew_point_temperature[idx] = dew_point;
+                    self.relative_humidity[idx] = relative_humidity(dry_bulb, dew_point);
+                }
+                HumidityRepair::TrustRelativeHumidity => {
+                    let rh = inconsistency.relative_humidity.min(100.);
+                    self.relative_humidity[idx] = rh;
+                    self.dew_point_temperature[idx] =
+                        dew_point_temperature(rh / 100. * saturation_vapor_pressure(dry_bulb));
+                }
+            }
+        }
+        inconsistencies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPWFile;
+    use std::fs;
+
+    fn _first_day() -> WeatherData {
+        let content = fs::read_to_string("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let lines: Vec<&str> = content.lines().take(32).collect();
+        EPWFile::from_reader(lines.join("\n").as_bytes())
+            .unwrap()
+            .data
+    }
+
+    #[test]
+    fn test_check_and_repair_humidity() {
+        let mut data = _first_day();
+        assert!(data
+            .check_humidity(DEFAULT_RELATIVE_HUMIDITY_TOLERANCE)
+            .is_empty());
+
+        data.dew_point_temperature[2] = data.dry_bulb_temperature[2] + 2.;
+        data.relative_humidity[5] = 20.;
+        let inconsistencies = data.check_humidity(DEFAULT_RELATIVE_HUMIDITY_TOLERANCE);
+        assert_eq!(2, inconsistencies.len());
+        assert_eq!(
+            HumidityIssue::DewPointAboveDryBulb,
+            inconsistencies[0].issue
+        );
+        assert!((inconsistencies[0].magnitude - 2.).abs() < 1e-9);
+        assert_eq!(data.timestamp[2], inconsistencies[0].timestamp);
+        assert_eq!(5, inconsistencies[1].index);
+        assert_eq!(
+            HumidityIssue::RelativeHumidityMismatch,
+            inconsistencies[1].issue
+        );
+
+        let original_dew_point = data.dew_point_temperature[5];
+        let mut trust_rh = _first_day();
+        trust_rh.dew_point_temperature = data.dew_point_temperature.clone();
+        trust_rh.relative_humidity = data.relative_humidity.clone();
+
+        data.repair_humidity(
+            HumidityRepair::TrustDewPoint,
+            DEFAULT_RELATIVE_HUMIDITY_TOLERANCE,
+        );
+        assert_eq!(data.dry_bulb_temperature[2], data.dew_point_temperature[2]);
+        assert!((data.relative_humidity[2] - 100.).abs() < 1e-9);
+        assert_eq!(original_dew_point, data.dew_point_temperature[5]);
+        assert!(data.relative_humidity[5] > 20.);
+
+        trust_rh.repair_humidity(
+            HumidityRepair::TrustRelativeHumidity,
+            DEFAULT_RELATIVE_HUMIDITY_TOLERANCE,
+        );
+        assert_eq!(20., trust_rh.relative_humidity[5]);
+        assert!(trust_rh.dew_point_temperature[5] < original_dew_point);
+        assert!(trust_rh.dew_point_temperature[2] <= trust_rh.dry_bulb_temperature[2]);
+        assert!(trust_rh
+            .check_humidity(DEFAULT_RELATIVE_HUMIDITY_TOLERANCE)
+            .is_empty());
+    }
+}
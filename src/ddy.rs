@@ -0,0 +1,799 @@
+/*!
+This module contains the parser and generator for `.ddy` files, the EnergyPlus design day files
+that are distributed alongside EPW files.
+
+A `.ddy` file is a fragment of an EnergyPlus IDF file. The parser reads the `Site:Location` object
+into a [SiteLocation] and every `SizingPeriod:DesignDay` object into a [DesignDay], all other
+objects are ignored. The field order is the one used since EnergyPlus 8.0.
+
+[DDYFile::from_header] creates the standard annual design days from the design conditions in an
+EPW header, which is useful for custom or morphed weather files that don't come with a `.ddy`.
+
+*/
+use crate::error::EPWParseError;
+use crate::psychrometrics::standard_pressure;
+use crate::Header;
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+
+const SITE_LOCATION_KEY: &str = "Site:Location";
+const DESIGN_DAY_KEY: &str = "SizingPeriod:DesignDay";
+
+/// Location of the weather station as given by the `Site:Location` object
+#[derive(Debug, PartialEq)]
+pub struct SiteLocation {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Time zone in hours relative to GMT
+    pub time_zone: f64,
+    pub elevation: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DayType {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Holiday,
+    SummerDesignDay,
+    WinterDesignDay,
+    CustomDay1,
+    CustomDay2,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DryBulbRangeModifierType {
+    DefaultMultipliers,
+    MultiplierSchedule,
+    DifferenceSchedule,
+    TemperatureProfileSchedule,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HumidityConditionType {
+    WetBulb,
+    DewPoint,
+    HumidityRatio,
+    Enthalpy,
+    RelativeHumiditySchedule,
+    WetBulbProfileDefaultMultipliers,
+    WetBulbProfileDifferenceSchedule,
+    WetBulbProfileMultiplierSchedule,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SolarModelIndicator {
+    ASHRAEClearSky,
+    ASHRAETau,
+    ASHRAETau2017,
+    ZhangHuang,
+    Schedule,
+}
+
+/// A `SizingPeriod:DesignDay` object
+///
+/// Temperatures are in °C, pressure in Pascals, wind speed in m/s and wind direction in degrees
+/// clockwise from north. Blank optional fields are represented as `None`.
+#[derive(Debug, PartialEq)]
+pub struct DesignDay {
+    pub name: String,
+    pub month: u32,
+    pub day_of_month: u32,
+    pub day_type: DayType,
+    pub maximum_dry_bulb_temperature: f64,
+    pub daily_dry_bulb_temperature_range: f64,
+    pub dry_bulb_temperature_range_modifier_type: DryBulbRangeModifierType,
+    pub dry_bulb_temperature_range_modifier_schedule: Option<String>,
+    pub humidity_condition_type: HumidityConditionType,
+    pub wetbulb_or_dewpoint_at_maximum_dry_bulb: Option<f64>,
+    pub humidity_condition_schedule: Option<String>,
+    pub humidity_ratio_at_maximum_dry_bulb: Option<f64>,
+    pub enthalpy_at_maximum_dry_bulb: Option<f64>,
+    pub daily_wet_bulb_temperature_range: Option<f64>,
+    pub barometric_pressure: f64,
+    pub wind_speed: f64,
+    pub wind_direction: f64,
+    pub rain: bool,
+    pub snow_on_ground: bool,
+    pub daylight_saving_time: bool,
+    pub solar_model: SolarModelIndicator,
+    pub beam_solar_schedule: Option<String>,
+    pub diffuse_solar_schedule: Option<String>,
+    /// ASHRAE clear sky optical depth for beam irradiance
+    pub taub: Option<f64>,
+    /// ASHRAE clear sky optical depth for diffuse irradiance
+    pub taud: Option<f64>,
+    pub sky_clearness: Option<f64>,
+}
+
+/// DDYFile is the representation of a parsed `.ddy` file
+#[derive(Debug)]
+pub struct DDYFile {
+    pub location: Option<SiteLocation>,
+    pub design_days: Vec<DesignDay>,
+}
+
+impl DDYFile {
+    /// Construct a DDYFile instance from a reader.
+    ///
+    /// ## Parameters
+    /// - `reader`: Reader that returns file contents.
+    ///
+    /// ## Returns
+    /// An initialized DDYFile or an EPWParseError
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, EPWParseError> {
+        let mut content: Vec<u8> = Vec::new();
+        if let Err(e) = reader.read_to_end(&mut content) {
+            return Err(EPWParseError::DesignDay(format!(
+                "Unable to read file: {}",
+                e
+            )));
+        }
+        let content = String::from_utf8_lossy(&content);
+
+        let mut location: Option<SiteLocation> = None;
+        let mut design_days: Vec<DesignDay> = Vec::new();
+        for object in _parse_idf_objects(&content) {
+            if object[0].eq_ignore_ascii_case(SITE_LOCATION_KEY) {
+                location = Some(_parse_site_location(&object)?);
+            } else if object[0].eq_ignore_ascii_case(DESIGN_DAY_KEY) {
+                design_days.push(_parse_design_day(&object)?);
+            }
+        }
+
+        Ok(Self {
+            location,
+            design_days,
+        })
+    }
+
+    /// Create a DDYFile instance from a file path
+    ///
+    /// ## Parameters
+    /// - `path`: Path to file on the filesystem
+    ///
+    /// ## Returns
+    /// An initialized DDYFile or an EPWParseError
+    pub fn from_path(path: &str) -> Result<Self, EPWParseError> {
+        let f = match fs::File::open(path) {
+            Ok(val) => val,
+            Err(e) => return Err(EPWParseError::FileNotFound(e.to_string())),
+        };
+        Self::from_reader(f)
+    }
+}
+
+impl DDYFile {
+    /// Generate the standard annual design days from the design conditions in an EPW header
+    ///
+    /// This creates the `Site:Location` object and the heating 99.6% and 99% and cooling 0.4%, 1%
+    /// and 2% dry bulb design days, following the conventions of the EnergyPlus weather
+    /// converter. Design days are placed on the 21st of the coldest and hottest month, at the
+    /// standard barometric pressure for the site elevation.
+    ///
+    /// ## Parameters
+    /// - `header`: Header of an EPW file with design conditions
+    ///
+    /// ## Returns
+    /// An initialized DDYFile or an EPWParseError if the header has no heating or cooling design
+    /// conditions
+    pub fn from_header(header: &Header) -> Result<Self, EPWParseError> {
+        let conditions = match &header.design_conditions {
+            Some(val) => val,
+            None => {
+                return Err(EPWParseError::DesignConditions(
+                    "No Design Conditions Found".to_string(),
+                ))
+            }
+        };
+        let heating = match &conditions.heating {
+            Some(val) => val,
+            None => {
+                return Err(EPWParseError::DesignConditions(
+                    "No Heating Design Conditions Found".to_string(),
+                ))
+            }
+        };
+        let cooling = match &conditions.cooling {
+            Some(val) => val,
+            None => {
+                return Err(EPWParseError::DesignConditions(
+                    "No Cooling Design Conditions Found".to_string(),
+                ))
+            }
+        };
+
+        let location = &header.location;
+        let barometric_pressure = standard_pressure(location.elevation).round();
+
+        let mut design_days: Vec<DesignDay> = Vec::with_capacity(5);
+        for (label, dry_bulb) in [
+            ("99.6%", heating.dry_bulb_99_6),
+            ("99%", heating.dry_bulb_99),
+        ] {
+            design_days.push(DesignDay {
+                name: format!("{} Ann Htg {} Condns DB", location.city, label),
+                month: heating.coldest_month,
+                day_of_month: 21,
+                day_type: DayType::WinterDesignDay,
+                maximum_dry_bulb_temperature: dry_bulb,
+                daily_dry_bulb_temperature_range: 0.,
+                dry_bulb_temperature_range_modifier_type:
+                    DryBulbRangeModifierType::DefaultMultipliers,
+                dry_bulb_temperature_range_modifier_schedule: None,
+                humidity_condition_type: HumidityConditionType::WetBulb,
+                wetbulb_or_dewpoint_at_maximum_dry_bulb: Some(dry_bulb),
+                humidity_condition_schedule: None,
+                humidity_ratio_at_maximum_dry_bulb: None,
+                enthalpy_at_maximum_dry_bulb: None,
+                daily_wet_bulb_temperature_range: None,
+                barometric_pressure,
+                wind_speed: heating.mean_coincident_wind_speed_99_6,
+                wind_direction: heating.prevailing_coincident_wind_direction_99_6,
+                rain: false,
+                snow_on_ground: false,
+                daylight_saving_time: false,
+                solar_model: SolarModelIndicator::ASHRAEClearSky,
+                beam_solar_schedule: None,
+                diffuse_solar_schedule: None,
+                taub: None,
+                taud: None,
+                sky_clearness: Some(0.),
+            });
+        }
+
+        for (label, dry_bulb, wet_bulb) in [
+            (
+                ".4%",
+                cooling.dry_bulb_0_4,
+                cooling.mean_coincident_wet_bulb_0_4,
+            ),
+            ("1%", cooling.dry_bulb_1, cooling.mean_coincident_wet_bulb_1),
+            ("2%", cooling.dry_bulb_2, cooling.mean_coincident_wet_bulb_2),
+        ] {
+            design_days.push(DesignDay {
+                name: format!("{} Ann Clg {} Condns DB=>MWB", location.city, label),
+                month: cooling.hottest_month,
+                day_of_month: 21,
+                day_type: DayType::SummerDesignDay,
+                maximum_dry_bulb_temperature: dry_bulb,
+                daily_dry_bulb_temperature_range: cooling.hottest_month_dry_bulb_range,
+                dry_bulb_temperature_range_modifier_type:
+                    DryBulbRangeModifierType::DefaultMultipliers,
+                dry_bulb_temperature_range_modifier_schedule: None,
+                humidity_condition_type: HumidityConditionType::WetBulb,
+                wetbulb_or_dewpoint_at_maximum_dry_bulb: Some(wet_bulb),
+                humidity_condition_schedule: None,
+                humidity_ratio_at_maximum_dry_bulb: None,
+                enthalpy_at_maximum_dry_bulb: None,
+                daily_wet_bulb_temperature_range: None,
+                barometric_pressure,
+                wind_speed: cooling.mean_coincident_wind_speed_0_4,
+                wind_direction: cooling.prevailing_coincident_wind_direction_0_4,
+                rain: false,
+                snow_on_ground: false,
+                daylight_saving_time: false,
+                solar_model: SolarModelIndicator::ASHRAEClearSky,
+                beam_solar_schedule: None,
+                diffuse_solar_schedule: None,
+                taub: None,
+                taud: None,
+                sky_clearness: Some(1.),
+            });
+        }
+
+        Ok(Self {
+            location: Some(SiteLocation {
+                name: format!(
+                    "{}_{}_{} Design_Conditions",
+                    location.city, location.state_province_region, location.country
+                ),
+                latitude: location.latitude,
+                longitude: location.longitude,
+                time_zone: location.time_zone.local_minus_utc() as f64 / 3600.,
+                elevation: location.elevation,
+            }),
+            design_days,
+        })
+    }
+
+    /// Write the location and design days as EnergyPlus IDF text
+    ///
+    /// ## Parameters
+    /// - `writer`: Destination for the IDF content
+    ///
+    /// ## Returns
+    /// Nothing, or the IO error raised by the writer
+    pub fn to_idf<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if let Some(location) = &self.location {
+            writeln!(writer, "{}", location)?;
+        }
+        for design_day in &self.design_days {
+            writeln!(writer, "{}", design_day)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SiteLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        _write_idf_object(
+            f,
+            SITE_LOCATION_KEY,
+            &[
+                (self.name.clone(), "Location Name"),
+                (self.latitude.to_string(), "Latitude {N+ S-}"),
+                (self.longitude.to_string(), "Longitude {W- E+}"),
+                (
+                    self.time_zone.to_string(),
+                    "Time Zone Relative to GMT {GMT+/-}",
+                ),
+                (self.elevation.to_string(), "Elevation {m}"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for DesignDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The variant names of the enums match the IDF keywords
+        _write_idf_object(
+            f,
+            DESIGN_DAY_KEY,
+            &[
+                (self.name.clone(), "Name"),
+                (self.month.to_string(), "Month"),
+                (self.day_of_month.to_string(), "Day of Month"),
+                (format!("{:?}", self.day_type), "Day Type"),
+                (
+                    self.maximum_dry_bulb_temperature.to_string(),
+                    "Maximum Dry-Bulb Temperature {C}",
+                ),
+                (
+                    self.daily_dry_bulb_temperature_range.to_string(),
+                    "Daily Dry-Bulb Temperature Range {deltaC}",
+                ),
+                (
+                    format!("{:?}", self.dry_bulb_temperature_range_modifier_type),
+                    "Dry-Bulb Temperature Range Modifier Type",
+                ),
+                (
+                    _optional_text(&self.dry_bulb_temperature_range_modifier_schedule),
+                    "Dry-Bulb Temperature Range Modifier Day Schedule Name",
+                ),
+                (
+                    format!("{:?}", self.humidity_condition_type),
+                    "Humidity Condition Type",
+                ),
+                (
+                    _optional_number(self.wetbulb_or_dewpoint_at_maximum_dry_bulb),
+                    "Wetbulb or DewPoint at Maximum Dry-Bulb {C}",
+                ),
+                (
+                    _optional_text(&self.humidity_condition_schedule),
+                    "Humidity Condition Day Schedule Name",
+                ),
+                (
+                    _optional_number(self.humidity_ratio_at_maximum_dry_bulb),
+                    "Humidity Ratio at Maximum Dry-Bulb {kgWater/kgDryAir}",
+                ),
+                (
+                    _optional_number(self.enthalpy_at_maximum_dry_bulb),
+                    "Enthalpy at Maximum Dry-Bulb {J/kg}",
+                ),
+                (
+                    _optional_number(self.daily_wet_bulb_temperature_range),
+                    "Daily Wet-Bulb Temperature Range {deltaC}",
+                ),
+                (
+                    self.barometric_pressure.to_string(),
+                    "Barometric Pressure {Pa}",
+                ),
+                (self.wind_speed.to_string(), "Wind Speed {m/s}"),
+                (
+                    self.wind_direction.to_string(),
+                    "Wind Direction {Degrees; N=0, S=180}",
+                ),
+                (_yes_no(self.rain), "Rain {Yes/No}"),
+                (_yes_no(self.snow_on_ground), "Snow on ground {Yes/No}"),
+                (
+                    _yes_no(self.daylight_saving_time),
+                    "Daylight Savings Time Indicator",
+                ),
+                (format!("{:?}", self.solar_model), "Solar Model Indicator"),
+                (
+                    _optional_text(&self.beam_solar_schedule),
+                    "Beam Solar Day Schedule Name",
+                ),
+                (
+                    _optional_text(&self.diffuse_solar_schedule),
+                    "Diffuse Solar Day Schedule Name",
+                ),
+                (
+                    _optional_number(self.taub),
+                    "ASHRAE Clear Sky Optical Depth for Beam Irradiance (taub)",
+                ),
+                (
+                    _optional_number(self.taud),
+                    "ASHRAE Clear Sky Optical Depth for Diffuse Irradiance (taud)",
+                ),
+                (_optional_number(self.sky_clearness), "Sky Clearness"),
+            ],
+        )
+    }
+}
+
+fn _write_idf_object(
+    f: &mut fmt::Formatter,
+    class: &str,
+    fields: &[(String, &str)],
+) -> fmt::Result {
+    writeln!(f, " {},", class)?;
+    for (idx, (value, comment)) in fields.iter().enumerate() {
+        let separator = match idx + 1 == fields.len() {
+            true => ";",
+            false => ",",
+        };
+        writeln!(f, "  {:>10}{} !- {}", value, separator, comment)?;
+    }
+    Ok(())
+}
+
+fn _optional_text(value: &Option<String>) -> String {
+    match value {
+        Some(val) => val.clone(),
+        None => String::new(),
+    }
+}
+
+fn _optional_number(value: Option<f64>) -> String {
+    match value {
+        Some(val) => val.to_string(),
+        None => String::new(),
+    }
+}
+
+fn _yes_no(value: bool) -> String {
+    match value {
+        true => "Yes".to_string(),
+        false => "No".to_string(),
+    }
+}
+
+/// Split IDF text into objects, each a list of fields starting with the class name.
+fn _parse_idf_objects(content: &str) -> Vec<Vec<String>> {
+    let stripped: Vec<&str> = content
+        .lines()
+        .map(|line| match line.find('!') {
+            Some(idx) => &line[..idx],
+            None => line,
+        })
+        .collect();
+
+    stripped
+        .join("\n")
+        .split(';')
+        .map(|object| {
+            object
+                .split(',')
+                .map(|field| field.trim().to_string())
+                .collect::<Vec<String>>()
+        })
+        .filter(|fields| !fields[0].is_empty())
+        .collect()
+}
+
+fn _parse_site_location(fields: &[String]) -> Result<SiteLocation, EPWParseError> {
+    if fields.len() < 6 {
+        return Err(EPWParseError::DesignDay(format!(
+            "Invalid Site:Location object: {}",
+            fields.join(",")
+        )));
+    }
+
+    Ok(SiteLocation {
+        name: fields[1].to_string(),
+        latitude: _parse_required(fields, 2, "Latitude")?,
+        longitude: _parse_required(fields, 3, "Longitude")?,
+        time_zone: _parse_required(fields, 4, "Time Zone")?,
+        elevation: _parse_required(fields, 5, "Elevation")?,
+    })
+}
+
+fn _parse_design_day(fields: &[String]) -> Result<DesignDay, EPWParseError> {
+    if fields.len() < 5 {
+        return Err(EPWParseError::DesignDay(format!(
+            "Invalid SizingPeriod:DesignDay object: {}",
+            fields.join(",")
+        )));
+    }
+
+    let day_type = match _field(fields, 4).to_ascii_lowercase().as_str() {
+        "sunday" => DayType::Sunday,
+        "monday" => DayType::Monday,
+        "tuesday" => DayType::Tuesday,
+        "wednesday" => DayType::Wednesday,
+        "thursday" => DayType::Thursday,
+        "friday" => DayType::Friday,
+        "saturday" => DayType::Saturday,
+        "holiday" => DayType::Holiday,
+        "summerdesignday" => DayType::SummerDesignDay,
+        "winterdesignday" => DayType::WinterDesignDay,
+        "customday1" => DayType::CustomDay1,
+        "customday2" => DayType::CustomDay2,
+        e => return Err(EPWParseError::DesignDay(format!("Invalid Day Type: {}", e))),
+    };
+
+    let dry_bulb_temperature_range_modifier_type =
+        match _field(fields, 7).to_ascii_lowercase().as_str() {
+            "" | "defaultmultipliers" => DryBulbRangeModifierType::DefaultMultipliers,
+            "multiplierschedule" => DryBulbRangeModifierType::MultiplierSchedule,
+            "differenceschedule" => DryBulbRangeModifierType::DifferenceSchedule,
+            "temperatureprofileschedule" => DryBulbRangeModifierType::TemperatureProfileSchedule,
+            e => {
+                return Err(EPWParseError::DesignDay(format!(
+                    "Invalid Dry-Bulb Temperature Range Modifier Type: {}",
+                    e
+                )))
+            }
+        };
+
+    let humidity_condition_type = match _field(fields, 9).to_ascii_lowercase().as_str() {
+        "" | "wetbulb" => HumidityConditionType::WetBulb,
+        "dewpoint" => HumidityConditionType::DewPoint,
+        "humidityratio" => HumidityConditionType::HumidityRatio,
+        "enthalpy" => HumidityConditionType::Enthalpy,
+        "relativehumidityschedule" => HumidityConditionType::RelativeHumiditySchedule,
+        "wetbulbprofiledefaultmultipliers" => {
+            HumidityConditionType::WetBulbProfileDefaultMultipliers
+        }
+        "wetbulbprofiledifferenceschedule" => {
+            HumidityConditionType::WetBulbProfileDifferenceSchedule
+        }
+        "wetbulbprofilemultiplierschedule" => {
+            HumidityConditionType::WetBulbProfileMultiplierSchedule
+        }
+        e => {
+            return Err(EPWParseError::DesignDay(format!(
+                "Invalid Humidity Condition Type: {}",
+                e
+            )))
+        }
+    };
+
+    let solar_model = match _field(fields, 21).to_ascii_lowercase().as_str() {
+        "" | "ashraeclearsky" => SolarModelIndicator::ASHRAEClearSky,
+        "ashraetau" => SolarModelIndicator::ASHRAETau,
+        "ashraetau2017" => SolarModelIndicator::ASHRAETau2017,
+        "zhanghuang" => SolarModelIndicator::ZhangHuang,
+        "schedule" => SolarModelIndicator::Schedule,
+        e => {
+            return Err(EPWParseError::DesignDay(format!(
+                "Invalid Solar Model Indicator: {}",
+                e
+            )))
+        }
+    };
+
+    Ok(DesignDay {
+        name: fields[1].to_string(),
+        month: _parse_required(fields, 2, "Month")?,
+        day_of_month: _parse_required(fields, 3, "Day of Month")?,
+        day_type,
+        maximum_dry_bulb_temperature: _parse_required(fields, 5, "Maximum Dry-Bulb Temperature")?,
+        daily_dry_bulb_temperature_range: _parse_optional(
+            fields,
+            6,
+            "Daily Dry-Bulb Temperature Range",
+        )?
+        .unwrap_or(0.),
+        dry_bulb_temperature_range_modifier_type,
+        dry_bulb_temperature_range_modifier_schedule: _optional_string(fields, 8),
+        humidity_condition_type,
+        wetbulb_or_dewpoint_at_maximum_dry_bulb: _parse_optional(
+            fields,
+            10,
+            "Wetbulb or DewPoint at Maximum Dry-Bulb",
+        )?,
+        humidity_condition_schedule: _optional_string(fields, 11),
+        humidity_ratio_at_maximum_dry_bulb: _parse_optional(
+            fields,
+            12,
+            "Humidity Ratio at Maximum Dry-Bulb",
+        )?,
+        enthalpy_at_maximum_dry_bulb: _parse_optional(fields, 13, "Enthalpy at Maximum Dry-Bulb")?,
+        daily_wet_bulb_temperature_range: _parse_optional(
+            fields,
+            14,
+            "Daily Wet-Bulb Temperature Range",
+        )?,
+        barometric_pressure: _parse_required(fields, 15, "Barometric Pressure")?,
+        wind_speed: _parse_required(fields, 16, "Wind Speed")?,
+        wind_direction: _parse_optional(fields, 17, "Wind Direction")?.unwrap_or(0.),
+        rain: _parse_yes_no(fields, 18, "Rain Indicator")?,
+        snow_on_ground: _parse_yes_no(fields, 19, "Snow Indicator")?,
+        daylight_saving_time: _parse_yes_no(fields, 20, "Daylight Saving Time Indicator")?,
+        solar_model,
+        beam_solar_schedule: _optional_string(fields, 22),
+        diffuse_solar_schedule: _optional_string(fields, 23),
+        taub: _parse_optional(
+            fields,
+            24,
+            "ASHRAE Clear Sky Optical Depth for Beam Irradiance",
+        )?,
+        taud: _parse_optional(
+            fields,
+            25,
+            "ASHRAE Clear Sky Optical Depth for Diffuse Irradiance",
+        )?,
+        sky_clearness: _parse_optional(fields, 26, "Sky Clearness")?,
+    })
+}
+
+fn _field(fields: &[String], idx: usize) -> &str {
+    match fields.get(idx) {
+        Some(val) => val.as_str(),
+        None => "",
+    }
+}
+
+fn _optional_string(fields: &[String], idx: usize) -> Option<String> {
+    match _field(fields, idx) {
+        "" => None,
+        val => Some(val.to_string()),
+    }
+}
+
+fn _parse_required<T: std::str::FromStr>(
+    fields: &[String],
+    idx: usize,
+    name: &str,
+) -> Result<T, EPWParseError>
+where
+    T::Err: std::fmt::Display,
+{
+    match _field(fields, idx).parse() {
+        Ok(val) => Ok(val),
+        Err(e) => Err(EPWParseError::DesignDay(format!(
+            "Invalid {}: {} [{}]",
+            name,
+            _field(fields, idx),
+            e
+        ))),
+    }
+}
+
+fn _parse_optional(
+    fields: &[String],
+    idx: usize,
+    name: &str,
+) -> Result<Option<f64>, EPWParseError> {
+    match _field(fields, idx) {
+        "" => Ok(None),
+        _ => Ok(Some(_parse_required(fields, idx, name)?)),
+    }
+}
+
+fn _parse_yes_no(fields: &[String], idx: usize, name: &str) -> Result<bool, EPWParseError> {
+    match _field(fields, idx).to_ascii_lowercase().as_str() {
+        "yes" => Ok(true),
+        "" | "no" => Ok(false),
+        e => Err(EPWParseError::DesignDay(format!("Invalid {}: {}", name, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_DDY: &str = "
+ ! Tampa Intl Ap_FL_USA Extreme Annual Wind Speeds, 1%=8.2m/s, 2.5%=7.4m/s, 5%=6.7m/s
+
+ Site:Location,
+  Tampa Intl Ap_FL_USA Design_Conditions,     !- Location Name
+      27.96,     !- Latitude {N+ S-}
+     -82.54,     !- Longitude {W- E+}
+      -5.00,     !- Time Zone Relative to GMT {GMT+/-}
+       3.00;     !- Elevation {m}
+
+ SizingPeriod:DesignDay,
+  Tampa Intl Ap Ann Htg 99.6% Condns DB,     !- Name
+         1,      !- Month
+        21,      !- Day of Month
+  WinterDesignDay,!- Day Type
+        3.2,      !- Maximum Dry-Bulb Temperature {C}
+        0.0,      !- Daily Dry-Bulb Temperature Range {C}
+ DefaultMultipliers, !- Dry-Bulb Temperature Range Modifier Type
+           ,      !- Dry-Bulb Temperature Range Modifier Day Schedule Name
+    Wetbulb,      !- Humidity Condition Type
+        3.2,      !- Wetbulb or DewPoint at Maximum Dry-Bulb {C}
+           ,      !- Humidity Condition Day Schedule Name
+           ,      !- Humidity Ratio at Maximum Dry-Bulb {kgWater/kgDryAir}
+           ,      !- Enthalpy at Maximum Dry-Bulb {J/kg}
+           ,      !- Daily Wet-Bulb Temperature Range {deltaC}
+    101290.,      !- Barometric Pressure {Pa}
+        2.6,      !- Wind Speed {m/s} design conditions vs. traditional 6.71 m/s (15 mph)
+          0,      !- Wind Direction {Degrees; N=0, S=180}
+         No,      !- Rain {Yes/No}
+         No,      !- Snow on ground {Yes/No}
+         No,      !- Daylight Savings Time Indicator
+  ASHRAEClearSky, !- Solar Model Indicator
+           ,      !- Beam Solar Day Schedule Name
+           ,      !- Diffuse Solar Day Schedule Name
+           ,      !- ASHRAE Clear Sky Optical Depth for Beam Irradiance (taub)
+           ,      !- ASHRAE Clear Sky Optical Depth for Diffuse Irradiance (taud)
+       0.00;      !- Clearness {0.0 to 1.2}
+";
+
+    #[test]
+    fn test_generate_ddy_from_header() {
+        let epw = crate::EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let generated = DDYFile::from_header(&epw.header).unwrap();
+        assert_eq!(5, generated.design_days.len());
+
+        let mut idf: Vec<u8> = Vec::new();
+        generated.to_idf(&mut idf).unwrap();
+        let ddy = DDYFile::from_reader(idf.as_slice()).unwrap();
+
+        assert_eq!(generated.location, ddy.location);
+        assert_eq!(generated.design_days, ddy.design_days);
+
+        let heating = &ddy.design_days[0];
+        assert_eq!("TAMPA Ann Htg 99.6% Condns DB", heating.name);
+        assert_eq!(1, heating.month);
+        assert_eq!(2.6, heating.maximum_dry_bulb_temperature);
+        assert_eq!(101289., heating.barometric_pressure);
+
+        let cooling = &ddy.design_days[2];
+        assert_eq!("TAMPA Ann Clg .4% Condns DB=>MWB", cooling.name);
+        assert_eq!(7, cooling.month);
+        assert_eq!(DayType::SummerDesignDay, cooling.day_type);
+        assert_eq!(33.6, cooling.maximum_dry_bulb_temperature);
+        assert_eq!(8.1, cooling.daily_dry_bulb_temperature_range);
+        assert_eq!(Some(25.3), cooling.wetbulb_or_dewpoint_at_maximum_dry_bulb);
+    }
+
+    #[test]
+    fn test_parse_ddy() {
+        let ddy = DDYFile::from_reader(TEST_DDY.as_bytes()).unwrap();
+
+        assert_eq!(
+            Some(SiteLocation {
+                name: "Tampa Intl Ap_FL_USA Design_Conditions".to_string(),
+                latitude: 27.96,
+                longitude: -82.54,
+                time_zone: -5.,
+                elevation: 3.,
+            }),
+            ddy.location
+        );
+
+        assert_eq!(1, ddy.design_days.len());
+        let design_day = &ddy.design_days[0];
+        assert_eq!("Tampa Intl Ap Ann Htg 99.6% Condns DB", design_day.name);
+        assert_eq!(1, design_day.month);
+        assert_eq!(21, design_day.day_of_month);
+        assert_eq!(DayType::WinterDesignDay, design_day.day_type);
+        assert_eq!(3.2, design_day.maximum_dry_bulb_temperature);
+        assert_eq!(
+            HumidityConditionType::WetBulb,
+            design_day.humidity_condition_type
+        );
+        assert_eq!(
+            Some(3.2),
+            design_day.wetbulb_or_dewpoint_at_maximum_dry_bulb
+        );
+        assert_eq!(None, design_day.humidity_ratio_at_maximum_dry_bulb);
+        assert_eq!(101290., design_day.barometric_pressure);
+        assert_eq!(2.6, design_day.wind_speed);
+        assert!(!design_day.rain);
+        assert_eq!(SolarModelIndicator::ASHRAEClearSky, design_day.solar_model);
+        assert_eq!(Some(0.), design_day.sky_clearness);
+    }
+}
@@ -0,0 +1,392 @@
+/*!
+Decomposition of global horizontal irradiance into its direct normal and diffuse horizontal
+components.
+
+Some sources only give the global horizontal radiation. The [DecompositionModel]s estimate the
+direct normal and diffuse horizontal radiation from it using the clearness index, the ratio of the
+global horizontal radiation to the extraterrestrial horizontal radiation, and the position of the
+sun. The components always add back up to the global horizontal radiation:
+`GHI = DHI + DNI cos(zenith)`.
+
+Records where the sun is below the horizon or less than 3° above it are treated as entirely
+diffuse. Records with a missing global or extraterrestrial radiation value give NaN.
+
+The DIRINT model (Perez et al., 1992) corrects the DISC estimate with an empirical coefficient for
+the bin of the record's zenith independent clearness index, zenith angle, clearness stability and
+precipitable water. The published table of 6 × 6 × 7 × 5 coefficients is not distributed with this
+crate and must be given as a [DirintCoefficients], indexed in that order.
+
+When only the missing values are filled and one of the direct normal and diffuse horizontal
+radiation values of a record is measured, the other one is derived from it and the global
+horizontal radiation rather than from the model, so that the three stay consistent.
+
+*/
+use crate::psychrometrics::standard_pressure;
+use crate::solar::{relative_air_mass, SolarPosition, SOLAR_CONSTANT};
+use crate::{EPWFile, WeatherData};
+
+/// Largest zenith angle in degrees at which the global radiation is split into components
+const MAX_ZENITH: f64 = 87.;
+
+/// Model used to split the global horizontal irradiance
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecompositionModel {
+    /// Diffuse fraction as a polynomial of the clearness index (Erbs, Klein and Duffie, 1982)
+    Erbs,
+    /// Direct beam clearness from the clearness index and air mass (Maxwell, 1987)
+    Disc,
+    /// Diffuse fraction from the clearness index, apparent solar time, zenith angle and the
+    /// departure from clear sky conditions (Engerer, 2015), with the coefficients refitted to
+    /// hourly data by Bright and Engerer (2019). The clear sky irradiance is estimated with the
+    /// Haurwitz model.
+    Engerer,
+    /// DISC corrected for the clearness stability and precipitable water (Perez et al., 1992)
+    Dirint(&'static DirintCoefficients),
+}
+
+/// DIRINT coefficients indexed by the bins of the zenith independent clearness index (6), the
+/// zenith angle (6), the clearness stability index (7, the last one when it can't be computed) and
+/// the precipitable water (5, the last one when it is missing)
+pub type DirintCoefficients = [[[[f64; 5]; 7]; 6]; 6];
+
+/// Direct normal and diffuse horizontal radiation estimated for each record
+#[derive(Debug)]
+pub struct DecomposedIrradiance {
+    pub direct_normal: Vec<f64>,
+    pub diffuse_horizontal: Vec<f64>,
+}
+
+impl WeatherData {
+    /// Estimate the direct normal and diffuse horizontal radiation from the global horizontal
+    /// radiation
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    /// - `model`: Decomposition model
+    ///
+    /// ## Returns
+    /// The estimated direct normal and diffuse horizontal radiation for each record
+    pub fn decompose_irradiance(
+        &self,
+        solar_position: &[SolarPosition],
+        model: DecompositionModel,
+    ) -> DecomposedIrradiance {
+        let mut decomposed = DecomposedIrradiance {
+            direct_normal: Vec::with_capacity(solar_position.len()),
+            diffuse_horizontal: Vec::with_capacity(solar_position.len()),
+        };
+
+        let kt_prime: Vec<f64> = match model {
+            DecompositionModel::Dirint(_) => self._zenith_independent_clearness(solar_position),
+            _ => Vec::new(),
+        };
+
+        for (idx, sun) in solar_position.iter().enumerate() {
+            let ghi = self.global_horizontal_radiation[idx];
+            let etr = self.extraterrestrial_horizontal_radiation[idx];
+            if ghi.is_nan() || etr.is_nan() {
+                decomposed.direct_normal.push(f64::NAN);
+                decomposed.diffuse_horizontal.push(f64::NAN);
+                continue;
+            }
+            if sun.zenith > MAX_ZENITH || ghi <= 0. || etr <= 0. {
+                decomposed.direct_normal.push(0.);
+                decomposed.diffuse_horizontal.push(ghi.max(0.));
+                continue;
+            }
+
+            let cos_zenith = sun.zenith.to_radians().cos();
+            let clearness = (ghi / etr).min(1.);
+            let pressure = match self.atmospheric_pressure[idx].is_nan() {
+                true => standard_pressure(0.),
+                false => self.atmospheric_pressure[idx],
+            };
+            let dni = match model {
+                DecompositionModel::Erbs => ghi * (1. - _erbs(clearness)) / cos_zenith,
+                DecompositionModel::Disc => {
+                    etr / cos_zenith * _disc(clearness, sun.zenith, pressure)
+                }
+                DecompositionModel::Engerer => {
+                    ghi * (1. - _engerer(ghi, clearness, etr, sun)) / cos_zenith
+                }
+                DecompositionModel::Dirint(coefficients) => {
+                    let coefficient = _dirint_coefficient(
+                        coefficients,
+                        &kt_prime,
+                        idx,
+                        sun.zenith,
+                        self.dew_point_temperature[idx],
+                    );
+                    etr / cos_zenith * _disc(clearness, sun.zenith, pressure) * coefficient
+                }
+            };
+            let dni = dni.clamp(0., ghi / cos_zenith);
+
+            decomposed.direct_normal.push(dni);
+            decomposed.diffuse_horizontal.push(ghi - dni * cos_zenith);
+        }
+        decomposed
+    }
+
+    /// Replace the direct normal and diffuse horizontal radiation with values estimated from the
+    /// global horizontal radiation
+    ///
+    /// With `only_missing`, a record missing only one of the two components gets it from the
+    /// global horizontal radiation and the other component, `GHI = DHI + DNI cos(zenith)`.
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    /// - `model`: Decomposition model
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_irradiance(
+        &mut self,
+        solar_position: &[SolarPosition],
+        model: DecompositionModel,
+        only_missing: bool,
+    ) {
+        let decomposed = self.decompose_irradiance(solar_position, model);
+        for (idx, sun) in solar_position.iter().enumerate() {
+            let ghi = self.global_horizontal_radiation[idx];
+            let dni = self.direct_normal_radiation[idx];
+            let dhi = self.diffuse_horizontal_radiation[idx];
+            let cos_zenith = sun.zenith.to_radians().cos().max(0.);
+            let (dni, dhi) = match (dni.is_nan(), dhi.is_nan()) {
+                _ if !only_missing => (
+                    decomposed.direct_normal[idx],
+                    decomposed.diffuse_horizontal[idx],
+                ),
+                (true, false) if sun.zenith > MAX_ZENITH => (0., dhi),
+                (true, false) => (((ghi - dhi) / cos_zenith).max(0.), dhi),
+                (false, true) => (dni, (ghi - dni * cos_zenith).max(0.)),
+                (true, true) => (
+                    decomposed.direct_normal[idx],
+                    decomposed.diffuse_horizontal[idx],
+                ),
+                (false, false) => (dni, dhi),
+            };
+            self.direct_normal_radiation[idx] = dni;
+            self.diffuse_horizontal_radiation[idx] = dhi;
+        }
+    }
+
+    /// Zenith independent clearness index kt' of each record (Perez et al., 1990), NaN when the
+    /// global or extraterrestrial radiation is missing or the sun is too low
+    fn _zenith_independent_clearness(&self, solar_position: &[SolarPosition]) -> Vec<f64> {
+        solar_position
+            .iter()
+            .enumerate()
+            .map(|(idx, sun)| {
+                let ghi = self.global_horizontal_radiation[idx];
+                let etr = self.extraterrestrial_horizontal_radiation[idx];
+                if sun.zenith > MAX_ZENITH || etr <= 0. || ghi.is_nan() || etr.is_nan() {
+                    return f64::NAN;
+                }
+                let air_mass = relative_air_mass(sun.zenith);
+                let clearness = (ghi / etr).clamp(0., 1.);
+                (clearness / (1.031 * (-1.4 / (0.9 + 9.4 / air_mass)).exp() + 0.1)).min(0.82)
+            })
+            .collect()
+    }
+}
+
+impl EPWFile {
+    /// Estimate the direct normal and diffuse horizontal radiation from the global horizontal
+    /// radiation
+    ///
+    /// ## Parameters
+    /// - `model`: Decomposition model
+    ///
+    /// ## Returns
+    /// The estimated direct normal and diffuse horizontal radiation for each record
+    pub fn decompose_irradiance(&self, model: DecompositionModel) -> DecomposedIrradiance {
+        self.data
+            .decompose_irradiance(&self.solar_position(), model)
+    }
+
+    /// Replace the direct normal and diffuse horizontal radiation with values estimated from the
+    /// global horizontal radiation
+    ///
+    /// ## Parameters
+    /// - `model`: Decomposition model
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_irradiance(&mut self, model: DecompositionModel, only_missing: bool) {
+        let solar_position = self.solar_position();
+        self.data
+            .fill_irradiance(&solar_position, model, only_missing);
+    }
+}
+
+/// DIRINT correction of the DISC direct normal radiation of a record
+///
+/// The clearness stability index is the mean absolute change of kt' from the previous and next
+/// records, or the change from the only one of them that has a kt'.
+fn _dirint_coefficient(
+    coefficients: &DirintCoefficients,
+    kt_prime: &[f64],
+    idx: usize,
+    zenith: f64,
+    dew_point: f64,
+) -> f64 {
+    let kt = kt_prime[idx];
+    let changes: Vec<f64> = [idx.checked_sub(1), Some(idx + 1)]
+        .iter()
+        .flatten()
+        .filter_map(|other| kt_prime.get(*other))
+        .map(|other| (kt - other).abs())
+        .filter(|change| !change.is_nan())
+        .collect();
+
+    let kt_bin = [0.24, 0.4, 0.56, 0.7, 0.8]
+        .iter()
+        .position(|bound| kt < *bound)
+        .unwrap_or(5);
+    let zenith_bin = [25., 40., 55., 70., 80.]
+        .iter()
+        .position(|bound| zenith < *bound)
+        .unwrap_or(5);
+    let stability_bin = match changes.is_empty() {
+        true => 6,
+        false => {
+            let stability = changes.iter().sum::<f64>() / changes.len() as f64;
+            [0.015, 0.035, 0.07, 0.15, 0.3]
+                .iter()
+                .position(|bound| stability < *bound)
+                .unwrap_or(5)
+        }
+    };
+    // Atmospheric precipitable water in cm
+    let water = (0.07 * dew_point - 0.075).exp();
+    let water_bin = match water.is_nan() {
+        true => 4,
+        false => [1., 2., 3.]
+            .iter()
+            .position(|bound| water < *bound)
+            .unwrap_or(3),
+    };
+    coefficients[kt_bin][zenith_bin][stability_bin][water_bin]
+}
+
+/// Diffuse fraction from the clearness index
+fn _erbs(clearness: f64) -> f64 {
+    match clearness {
+        kt if kt <= 0.22 => 1. - 0.09 * kt,
+        kt if kt <= 0.8 => {
+            0.9511 - 0.1604 * kt + 4.388 * kt.powi(2) - 16.638 * kt.powi(3) + 12.336 * kt.powi(4)
+        }
+        _ => 0.165,
+    }
+}
+
+/// Direct beam clearness, the ratio of the direct normal to extraterrestrial normal radiation
+fn _disc(clearness: f64, zenith: f64, pressure: f64) -> f64 {
+    let air_mass = (relative_air_mass(zenith) * pressure / standard_pressure(0.)).min(12.);
+    let kt = clearness;
+    let (a, b, c) = match kt <= 0.6 {
+        true => (
+            0.512 - 1.56 * kt + 2.286 * kt.powi(2) - 2.222 * kt.powi(3),
+            0.37 + 0.962 * kt,
+            -0.28 + 0.932 * kt - 2.048 * kt.powi(2),
+        ),
+        false => (
+            -5.743 + 21.77 * kt - 27.49 * kt.powi(2) + 11.56 * kt.powi(3),
+            41.4 - 118.5 * kt + 66.05 * kt.powi(2) + 31.9 * kt.powi(3),
+            -47.01 + 184.2 * kt - 222. * kt.powi(2) + 73.81 * kt.powi(3),
+        ),
+    };
+    let clear_sky = 0.866 - 0.122 * air_mass + 0.0121 * air_mass.powi(2)
+        - 0.000653 * air_mass.powi(3)
+        + 0.000014 * air_mass.powi(4);
+    clear_sky - (a + b * (c * air_mass).exp())
+}
+
+/// Diffuse fraction from the Engerer2 model with the hourly coefficients
+fn _engerer(ghi: f64, clearness: f64, etr: f64, sun: &SolarPosition) -> f64 {
+    const C: f64 = 0.10562;
+    const BETA: [f64; 6] = [-4.1332, 8.2578, 0.010087, 0.00088801, -4.9302, 0.44378];
+
+    // Clearness index of the Haurwitz clear sky, 1098 cos(z) exp(-0.057 / cos(z)) W/m², relative
+    // to the solar constant
+    let cos_zenith = sun.zenith.to_radians().cos();
+    let clear_sky_clearness = 1098. * (-0.057 / cos_zenith).exp() / SOLAR_CONSTANT;
+    let clear_sky = clear_sky_clearness * etr;
+    let apparent_solar_time = 12. + sun.hour_angle / 15.;
+    let cloud_enhancement = (1. - clear_sky / ghi).max(0.);
+
+    let exponent = BETA[0]
+        + BETA[1] * clearness
+        + BETA[2] * apparent_solar_time
+        + BETA[3] * sun.zenith
+        + BETA[4] * (clear_sky_clearness - clearness);
+    (C + (1. - C) / (1. + exponent.exp()) + BETA[5] * cloud_enhancement).clamp(0., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompose_irradiance() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let sun = epw.solar_position();
+        let noon = 12;
+
+        for model in [
+            DecompositionModel::Erbs,
+            DecompositionModel::Disc,
+            DecompositionModel::Engerer,
+        ] {
+            let decomposed = epw.decompose_irradiance(model);
+            assert_eq!(0., decomposed.direct_normal[0]);
+            assert_eq!(
+                epw.data.global_horizontal_radiation[0],
+                decomposed.diffuse_horizontal[0]
+            );
+
+            let dni = decomposed.direct_normal[noon];
+            let dhi = decomposed.diffuse_horizontal[noon];
+            assert!(dni > 0. && dhi > 0., "{:?} {} {}", model, dni, dhi);
+            assert!(
+                (epw.data.global_horizontal_radiation[noon]
+                    - dhi
+                    - dni * sun[noon].zenith.to_radians().cos())
+                .abs()
+                    < 1e-6
+            );
+        }
+
+        // With all its coefficients equal to 1, DIRINT is DISC
+        static ONES: DirintCoefficients = [[[[1.; 5]; 7]; 6]; 6];
+        let disc = epw.decompose_irradiance(DecompositionModel::Disc);
+        let dirint = epw.decompose_irradiance(DecompositionModel::Dirint(&ONES));
+        assert_eq!(disc.direct_normal, dirint.direct_normal);
+        // kt' bin 2, zenith bin 1, stability bin 1 and precipitable water bin 1
+        let mut coefficients: DirintCoefficients = [[[[1.; 5]; 7]; 6]; 6];
+        coefficients[2][1][1][1] = 0.8;
+        let factor = _dirint_coefficient(&coefficients, &[0.5, 0.52, 0.5], 1, 30., 10.);
+        assert_eq!(0.8, factor);
+        // Without neighbours or dew point, the last stability and precipitable water bins
+        coefficients[0][5][6][4] = 0.5;
+        let factor = _dirint_coefficient(&coefficients, &[0.1], 0, 85., f64::NAN);
+        assert_eq!(0.5, factor);
+
+        // A measured component is kept and the missing one follows from the global radiation
+        let original_dhi = epw.data.diffuse_horizontal_radiation.clone();
+        let original_dni = epw.data.direct_normal_radiation[noon + 1];
+        epw.data.direct_normal_radiation[noon] = f64::NAN;
+        epw.data.diffuse_horizontal_radiation[noon + 1] = f64::NAN;
+        epw.fill_irradiance(DecompositionModel::Erbs, true);
+        assert_eq!(
+            original_dhi[noon],
+            epw.data.diffuse_horizontal_radiation[noon]
+        );
+        assert_eq!(original_dni, epw.data.direct_normal_radiation[noon + 1]);
+        for idx in [noon, noon + 1] {
+            let data = &epw.data;
+            let closure = data.diffuse_horizontal_radiation[idx]
+                + data.direct_normal_radiation[idx] * sun[idx].zenith.to_radians().cos();
+            assert!((data.global_horizontal_radiation[idx] - closure).abs() < 1e-6);
+        }
+        epw.data.diffuse_horizontal_radiation[noon + 1] = original_dhi[noon + 1];
+        assert_eq!(original_dhi, epw.data.diffuse_horizontal_radiation);
+    }
+}
@@ -0,0 +1,252 @@
+/*!
+Heating and cooling degree days.
+
+Degree days measure how far, and for how long, the outdoor temperature is below (heating) or
+above (cooling) a base temperature. Three [DegreeDayMethod]s are available:
+- [DegreeDayMethod::DailyMean]: the difference between the base and the daily mean dry bulb
+  temperature, the method used by most published degree day tables
+- [DegreeDayMethod::HourlyIntegration]: the difference between the base and each record's dry
+  bulb temperature, integrated over the day. It is always at least the daily mean value, as it
+  also counts the hours below the base on days with a mean above it.
+- [DegreeDayMethod::Schoenau]: the monthly degree days estimated from the monthly mean temperature
+  and the standard deviation of the daily means (Schoenau and Kehrig, 1990), the method of the
+  ASHRAE Handbook of Fundamentals. It only defines monthly values, so the daily values are the
+  daily mean ones.
+
+Records are grouped into days by their timestamp and into months by calendar month, so a file
+spanning several years adds up the same month of different years. Missing temperatures are
+skipped, and a day without any temperature has NaN degree days.
+
+*/
+use crate::WeatherData;
+use chrono::{Datelike, NaiveDate};
+use std::f64::consts::PI;
+
+/// Coefficient of the Schoenau and Kehrig approximation
+const SCHOENAU_A: f64 = 1.698;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DegreeDayMethod {
+    DailyMean,
+    HourlyIntegration,
+    Schoenau,
+}
+
+/// Degree days for a single day
+#[derive(Debug, PartialEq)]
+pub struct DailyDegreeDays {
+    pub date: NaiveDate,
+    /// Mean dry bulb temperature of the day in °C
+    pub mean_temperature: f64,
+    pub heating: f64,
+    pub cooling: f64,
+}
+
+/// Degree days for a calendar month
+#[derive(Debug, PartialEq)]
+pub struct MonthlyDegreeDays {
+    /// Month number, 1 for January
+    pub month: u32,
+    pub heating: f64,
+    pub cooling: f64,
+}
+
+/// Heating and cooling degree days in °C·days, aggregated daily, monthly and annually
+#[derive(Debug)]
+pub struct DegreeDays {
+    pub heating_base: f64,
+    pub cooling_base: f64,
+    pub daily: Vec<DailyDegreeDays>,
+    pub monthly: Vec<MonthlyDegreeDays>,
+    pub annual_heating: f64,
+    pub annual_cooling: f64,
+}
+
+impl WeatherData {
+    /// Compute heating and cooling degree days from the dry bulb temperature
+    ///
+    /// ## Parameters
+    /// - `heating_base`: Base temperature for heating degree days in °C, e.g. 18
+    /// - `cooling_base`: Base temperature for cooling degree days in °C, e.g. 18 or 10
+    /// - `method`: Degree day method
+    ///
+    /// ## Returns
+    /// The daily, monthly and annual degree days
+    pub fn degree_days(
+        &self,
+        heating_base: f64,
+        cooling_base: f64,
+        method: DegreeDayMethod,
+    ) -> DegreeDays {
+        let mut daily: Vec<DailyDegreeDays> = Vec::new();
+        let mut day_temperatures: Vec<f64> = Vec::new();
+        for (idx, timestamp) in self.timestamp.iter().enumerate() {
+            let date = timestamp.date_naive();
+            if daily.last().map(|day| day.date) != Some(date) {
+                _finish_day(
+                    &mut daily,
+                    &day_temperatures,
+                    heating_base,
+                    cooling_base,
+                    method,
+                );
+                day_temperatures.clear();
+                daily.push(DailyDegreeDays {
+                    date,
+                    mean_temperature: f64::NAN,
+                    heating: f64::NAN,
+                    cooling: f64::NAN,
+                });
+            }
+            if !self.dry_bulb_temperature[idx].is_nan() {
+                day_temperatures.push(self.dry_bulb_temperature[idx]);
+            }
+        }
+        _finish_day(
+            &mut daily,
+            &day_temperatures,
+            heating_base,
+            cooling_base,
+            method,
+        );
+
+        let mut monthly: Vec<MonthlyDegreeDays> = Vec::new();
+        for month in 1..=12 {
+            let days: Vec<&DailyDegreeDays> = daily
+                .iter()
+                .filter(|day| day.date.month() == month && !day.mean_temperature.is_nan())
+                .collect();
+            if days.is_empty() {
+                continue;
+            }
+            let (heating, cooling) = match method {
+                DegreeDayMethod::Schoenau => {
+                    let means: Vec<f64> = days.iter().map(|day| day.mean_temperature).collect();
+                    (
+                        _schoenau(&means, heating_base, true),
+                        _schoenau(&means, cooling_base, false),
+                    )
+                }
+                _ => (
+                    days.iter().map(|day| day.heating).sum(),
+                    days.iter().map(|day| day.cooling).sum(),
+                ),
+            };
+            monthly.push(MonthlyDegreeDays {
+                month,
+                heating,
+                cooling,
+            });
+        }
+
+        DegreeDays {
+            heating_base,
+            cooling_base,
+            annual_heating: monthly.iter().map(|month| month.heating).sum(),
+            annual_cooling: monthly.iter().map(|month| month.cooling).sum(),
+            daily,
+            monthly,
+        }
+    }
+}
+
+/// Fill in the degree days of the last day from its temperatures
+fn _finish_day(
+    daily: &mut [DailyDegreeDays],
+    temperatures: &[f64],
+    heating_base: f64,
+    cooling_base: f64,
+    method: DegreeDayMethod,
+) {
+    let day = match daily.last_mut() {
+        Some(val) => val,
+        None => return,
+    };
+    if temperatures.is_empty() {
+        return;
+    }
+
+    let count = temperatures.len() as f64;
+    day.mean_temperature = temperatures.iter().sum::<f64>() / count;
+    match method {
+        DegreeDayMethod::HourlyIntegration => {
+            day.heating = temperatures
+                .iter()
+                .map(|t| (heating_base - t).max(0.))
+                .sum::<f64>()
+                / count;
+            day.cooling = temperatures
+                .iter()
+                .map(|t| (t - cooling_base).max(0.))
+                .sum::<f64>()
+                / count;
+        }
+        DegreeDayMethod::DailyMean | DegreeDayMethod::Schoenau => {
+            day.heating = (heating_base - day.mean_temperature).max(0.);
+            day.cooling = (day.mean_temperature - cooling_base).max(0.);
+        }
+    }
+}
+
+/// Monthly degree days from the daily mean temperatures of the month
+///
+/// Weather files don't give the standard deviation of the monthly mean temperature `σ_m` used by
+/// the method, so it is derived from the standard deviation of the daily means `σ_d`, such that the
+/// estimate is exact for normally distributed daily means when the monthly mean is at the base:
+/// `σ_m √N = σ_d 2a φ(0) / ln 2 ≈ 1.95 σ_d`, where `φ` is the standard normal density.
+fn _schoenau(daily_means: &[f64], base: f64, heating: bool) -> f64 {
+    let days = daily_means.len() as f64;
+    let mean = daily_means.iter().sum::<f64>() / days;
+    let variance = daily_means.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / days;
+    let difference = match heating {
+        true => base - mean,
+        false => mean - base,
+    };
+    if variance <= 0. {
+        return days * difference.max(0.);
+    }
+
+    let a = SCHOENAU_A;
+    let sigma = variance.sqrt() * 2. * a / (2. * PI).sqrt() / 2_f64.ln() / days.sqrt();
+    let z = difference / (sigma * days.sqrt());
+    sigma * days.powf(1.5) * (z / 2. + ((-a * z).exp() + (a * z).exp()).ln() / (2. * a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPWFile;
+
+    #[test]
+    fn test_degree_days() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let daily_mean = epw.data.degree_days(18., 18., DegreeDayMethod::DailyMean);
+        assert_eq!(365, daily_mean.daily.len());
+        assert_eq!(12, daily_mean.monthly.len());
+        let day = &daily_mean.daily[0];
+        assert_eq!(NaiveDate::from_ymd_opt(1987, 1, 1), Some(day.date));
+        assert!((day.heating - day.cooling - (18. - day.mean_temperature)).abs() < 1e-9);
+        let monthly_total: f64 = daily_mean.monthly.iter().map(|m| m.heating).sum();
+        assert!((daily_mean.annual_heating - monthly_total).abs() < 1e-9);
+
+        let hourly = epw
+            .data
+            .degree_days(18., 18., DegreeDayMethod::HourlyIntegration);
+        for (integrated, mean) in hourly.daily.iter().zip(&daily_mean.daily) {
+            assert!(integrated.heating >= mean.heating - 1e-9);
+            assert!(integrated.cooling >= mean.cooling - 1e-9);
+        }
+
+        // The Schoenau estimate of the annual values is close to the daily mean ones
+        let schoenau = epw.data.degree_days(18., 18., DegreeDayMethod::Schoenau);
+        assert_eq!(daily_mean.daily, schoenau.daily);
+        assert!(
+            (schoenau.annual_heating - daily_mean.annual_heating).abs()
+                < 0.05 * daily_mean.annual_heating
+        );
+        assert!(
+            (schoenau.annual_cooling - daily_mean.annual_cooling).abs()
+                < 0.05 * daily_mean.annual_cooling
+        );
+    }
+}
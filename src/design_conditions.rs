@@ -0,0 +1,422 @@
+/*!
+ASHRAE design conditions computed from the weather data, following the definitions of the ASHRAE
+Handbook of Fundamentals, chapter 14.
+
+The DESIGN CONDITIONS record of AMY, morphed and other generated files is often empty, or copied
+from the file the data was derived from. The values computed here can replace it, see
+[EPWFile::update_design_conditions].
+
+Design values are percentiles of the records, e.g. the 0.4% cooling dry bulb temperature is
+exceeded by 0.4% of the records and the 99.6% heating one by 99.6%. The monthly wind speeds are
+percentiles of the records of the coldest month. Mean coincident values are the means over the
+records within 0.5 units of the design value, e.g. the mean wet bulb temperature of the records
+within 0.5°C of the 0.4% dry bulb temperature, and prevailing coincident wind directions are the
+most frequent direction of these records, to the nearest 10°, ignoring calms.
+
+Humidity ratios at the design dew points are computed at the mean station pressure, or at the
+standard pressure of the station elevation when the pressure is missing. The number of hours
+between 8 a.m. and 4 p.m. with a dry bulb temperature between 12.8 and 20.6°C is scaled to a 365
+day year.
+
+The extreme annual dry bulb temperatures are the means and sample standard deviations over the
+calendar years of the data. Typical years, whose months come from different years, are a single
+year, so their standard deviations are NaN and the return period values are left empty.
+
+*/
+use crate::header::{
+    CoolingDesignConditions, DesignConditions, ExtremeDesignConditions, HeatingDesignConditions,
+    Location,
+};
+use crate::psychrometrics::{humidity_ratio, partial_vapor_pressure, standard_pressure};
+use crate::summary::{daily_dry_bulb_ranges, monthly_means, Statistics};
+use crate::{EPWFile, WeatherData};
+use chrono::{Datelike, Timelike};
+use std::f64::consts::PI;
+
+/// Source of computed design conditions
+pub const COMPUTED_DESIGN_CONDITIONS_SOURCE: &str = "Computed from the weather data";
+
+/// Half width of the bins around a design value used for the mean coincident values
+const COINCIDENT_BIN_HALF_WIDTH: f64 = 0.5;
+
+/// Return periods in years of the extreme dry bulb temperatures
+const RETURN_PERIODS: [f64; 4] = [5., 10., 20., 50.];
+
+impl WeatherData {
+    /// Compute the heating, cooling and extreme design conditions
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station, used for the pressure when it is missing
+    ///
+    /// ## Returns
+    /// The design conditions
+    pub fn compute_design_conditions(&self, location: &Location) -> DesignConditions {
+        let mut pressure = Statistics::from_values(&self.atmospheric_pressure).mean;
+        if pressure.is_nan() {
+            pressure = standard_pressure(location.elevation);
+        }
+        let wet_bulb = self.wet_bulb_temperature();
+
+        DesignConditions {
+            source: COMPUTED_DESIGN_CONDITIONS_SOURCE.to_string(),
+            heating: Some(self._heating_design_conditions(pressure)),
+            cooling: Some(self._cooling_design_conditions(pressure, &wet_bulb)),
+            extremes: Some(self._extreme_design_conditions(&wet_bulb)),
+        }
+    }
+
+    fn _heating_design_conditions(&self, pressure: f64) -> HeatingDesignConditions {
+        let dry_bulb = &self.dry_bulb_temperature;
+        let dew_point = &self.dew_point_temperature;
+        let monthly = monthly_means(&self.timestamp, dry_bulb);
+        let coldest_month = (0..12)
+            .filter(|idx| !monthly[*idx].is_nan())
+            .min_by(|a, b| monthly[*a].total_cmp(&monthly[*b]))
+            .map_or(1, |idx| idx as u32 + 1);
+
+        let in_month: Vec<usize> = (0..self.timestamp.len())
+            .filter(|idx| self.timestamp[*idx].month() == coldest_month)
+            .collect();
+        let month_wind: Vec<f64> = in_month.iter().map(|i| self.wind_speed[*i]).collect();
+        let month_dry_bulb: Vec<f64> = in_month.iter().map(|i| dry_bulb[*i]).collect();
+
+        let dry_bulb_99_6 = _percentile(dry_bulb, 0.004);
+        let dew_point_99_6 = _percentile(dew_point, 0.004);
+        let dew_point_99 = _percentile(dew_point, 0.01);
+        let wind_0_4 = _percentile(&month_wind, 0.996);
+        let wind_1 = _percentile(&month_wind, 0.99);
+
+        HeatingDesignConditions {
+            coldest_month,
+            dry_bulb_99_6,
+            dry_bulb_99: _percentile(dry_bulb, 0.01),
+            humidification_dew_point_99_6: dew_point_99_6,
+            humidification_humidity_ratio_99_6: _design_humidity_ratio(dew_point_99_6, pressure),
+            humidification_mean_coincident_dry_bulb_99_6: _mean_coincident(
+                dew_point,
+                dry_bulb,
+                dew_point_99_6,
+            ),
+            humidification_dew_point_99: dew_point_99,
+            humidification_humidity_ratio_99: _design_humidity_ratio(dew_point_99, pressure),
+            humidification_mean_coincident_dry_bulb_99: _mean_coincident(
+                dew_point,
+                dry_bulb,
+                dew_point_99,
+            ),
+            coldest_month_wind_speed_0_4: wind_0_4,
+            coldest_month_mean_coincident_dry_bulb_0_4: _mean_coincident(
+                &month_wind,
+                &month_dry_bulb,
+                wind_0_4,
+            ),
+            coldest_month_wind_speed_1: wind_1,
+            coldest_month_mean_coincident_dry_bulb_1: _mean_coincident(
+                &month_wind,
+                &month_dry_bulb,
+                wind_1,
+            ),
+            mean_coincident_wind_speed_99_6: _mean_coincident(
+                dry_bulb,
+                &self.wind_speed,
+                dry_bulb_99_6,
+            ),
+            prevailing_coincident_wind_direction_99_6: self
+                ._prevailing_coincident_wind_direction(dry_bulb, dry_bulb_99_6),
+        }
+    }
+
+    fn _cooling_design_conditions(
+        &self,
+        pressure: f64,
+        wet_bulb: &[f64],
+    ) -> CoolingDesignConditions {
+        let dry_bulb = &self.dry_bulb_temperature;
+        let dew_point = &self.dew_point_temperature;
+        let enthalpy = self.enthalpy();
+        let monthly = monthly_means(&self.timestamp, dry_bulb);
+        let hottest_month = (0..12)
+            .filter(|idx| !monthly[*idx].is_nan())
+            .max_by(|a, b| monthly[*a].total_cmp(&monthly[*b]))
+            .map_or(7, |idx| idx as u32 + 1);
+        let (day_months, day_ranges) = daily_dry_bulb_ranges(self);
+        let hottest_month_ranges = day_ranges
+            .iter()
+            .zip(&day_months)
+            .filter(|(_, month)| **month == hottest_month)
+            .map(|(range, _)| range);
+
+        let dry_bulb_design = [0.996, 0.99, 0.98].map(|p| _percentile(dry_bulb, p));
+        let wet_bulb_design = [0.996, 0.99, 0.98].map(|p| _percentile(wet_bulb, p));
+        let dew_point_design = [0.996, 0.99, 0.98].map(|p| _percentile(dew_point, p));
+        let enthalpy_design = [0.996, 0.99, 0.98].map(|p| _percentile(&enthalpy, p));
+        let coincident_wet_bulb = dry_bulb_design.map(|t| _mean_coincident(dry_bulb, wet_bulb, t));
+        let evaporation_dry_bulb = wet_bulb_design.map(|t| _mean_coincident(wet_bulb, dry_bulb, t));
+        let dehumidification_dry_bulb =
+            dew_point_design.map(|t| _mean_coincident(dew_point, dry_bulb, t));
+        let enthalpy_dry_bulb = enthalpy_design.map(|h| _mean_coincident(&enthalpy, dry_bulb, h));
+
+        let mild_daytime_records = (0..self.timestamp.len())
+            .filter(|idx| {
+                (8..16).contains(&self.timestamp[*idx].hour())
+                    && (12.8..=20.6).contains(&dry_bulb[*idx])
+            })
+            .count();
+
+        CoolingDesignConditions {
+            hottest_month,
+            hottest_month_dry_bulb_range: Statistics::from_values(hottest_month_ranges).mean,
+            dry_bulb_0_4: dry_bulb_design[0],
+            mean_coincident_wet_bulb_0_4: coincident_wet_bulb[0],
+            dry_bulb_1: dry_bulb_design[1],
+            mean_coincident_wet_bulb_1: coincident_wet_bulb[1],
+            dry_bulb_2: dry_bulb_design[2],
+            mean_coincident_wet_bulb_2: coincident_wet_bulb[2],
+            evaporation_wet_bulb_0_4: wet_bulb_design[0],
+            evaporation_mean_coincident_dry_bulb_0_4: evaporation_dry_bulb[0],
+            evaporation_wet_bulb_1: wet_bulb_design[1],
+            evaporation_mean_coincident_dry_bulb_1: evaporation_dry_bulb[1],
+            evaporation_wet_bulb_2: wet_bulb_design[2],
+            evaporation_mean_coincident_dry_bulb_2: evaporation_dry_bulb[2],
+            mean_coincident_wind_speed_0_4: _mean_coincident(
+                dry_bulb,
+                &self.wind_speed,
+                dry_bulb_design[0],
+            ),
+            prevailing_coincident_wind_direction_0_4: self
+                ._prevailing_coincident_wind_direction(dry_bulb, dry_bulb_design[0]),
+            dehumidification_dew_point_0_4: dew_point_design[0],
+            dehumidification_humidity_ratio_0_4: _design_humidity_ratio(
+                dew_point_design[0],
+                pressure,
+            ),
+            dehumidification_mean_coincident_dry_bulb_0_4: dehumidification_dry_bulb[0],
+            dehumidification_dew_point_1: dew_point_design[1],
+            dehumidification_humidity_ratio_1: _design_humidity_ratio(
+                dew_point_design[1],
+                pressure,
+            ),
+            dehumidification_mean_coincident_dry_bulb_1: dehumidification_dry_bulb[1],
+            dehumidification_dew_point_2: dew_point_design[2],
+            dehumidification_humidity_ratio_2: _design_humidity_ratio(
+                dew_point_design[2],
+                pressure,
+            ),
+            dehumidification_mean_coincident_dry_bulb_2: dehumidification_dry_bulb[2],
+            enthalpy_0_4: enthalpy_design[0],
+            enthalpy_mean_coincident_dry_bulb_0_4: enthalpy_dry_bulb[0],
+            enthalpy_1: enthalpy_design[1],
+            enthalpy_mean_coincident_dry_bulb_1: enthalpy_dry_bulb[1],
+            enthalpy_2: enthalpy_design[2],
+            enthalpy_mean_coincident_dry_bulb_2: enthalpy_dry_bulb[2],
+            hours_8_to_4_dry_bulb_12_8_to_20_6: Some(
+                mild_daytime_records as f64 * 8760. / self.timestamp.len().max(1) as f64,
+            ),
+        }
+    }
+
+    fn _extreme_design_conditions(&self, wet_bulb: &[f64]) -> ExtremeDesignConditions {
+        // Records of a typical year are not in chronological order
+        let chronological = self.timestamp.windows(2).all(|pair| pair[0] <= pair[1]);
+        let mut annual_minimum: Vec<f64> = Vec::new();
+        let mut annual_maximum: Vec<f64> = Vec::new();
+        let mut current_year: Option<i32> = None;
+        for (timestamp, dry_bulb) in self.timestamp.iter().zip(&self.dry_bulb_temperature) {
+            let year = match chronological {
+                true => Some(timestamp.year()),
+                false => None,
+            };
+            if annual_minimum.is_empty() || year != current_year {
+                current_year = year;
+                annual_minimum.push(f64::INFINITY);
+                annual_maximum.push(f64::NEG_INFINITY);
+            }
+            if let (Some(minimum), Some(maximum)) =
+                (annual_minimum.last_mut(), annual_maximum.last_mut())
+            {
+                *minimum = minimum.min(*dry_bulb);
+                *maximum = maximum.max(*dry_bulb);
+            }
+        }
+
+        let (mean_minimum, std_dev_minimum) = _mean_and_sample_std_dev(&annual_minimum);
+        let (mean_maximum, std_dev_maximum) = _mean_and_sample_std_dev(&annual_maximum);
+        let return_period_dry_bulb = match std_dev_minimum.is_nan() || std_dev_maximum.is_nan() {
+            true => Vec::new(),
+            false => RETURN_PERIODS
+                .iter()
+                .map(|n| {
+                    // Gumbel distribution of the annual extremes
+                    let f = -(6_f64.sqrt() / PI) * (0.5772 + (n / (n - 1.)).ln().ln());
+                    (
+                        mean_minimum - f * std_dev_minimum,
+                        mean_maximum + f * std_dev_maximum,
+                    )
+                })
+                .collect(),
+        };
+
+        ExtremeDesignConditions {
+            wind_speed_1: _percentile(&self.wind_speed, 0.99),
+            wind_speed_2_5: _percentile(&self.wind_speed, 0.975),
+            wind_speed_5: _percentile(&self.wind_speed, 0.95),
+            maximum_wet_bulb: Statistics::from_values(wet_bulb).maximum,
+            mean_minimum_dry_bulb: mean_minimum,
+            mean_maximum_dry_bulb: mean_maximum,
+            std_dev_minimum_dry_bulb: std_dev_minimum,
+            std_dev_maximum_dry_bulb: std_dev_maximum,
+            return_period_dry_bulb,
+        }
+    }
+
+    /// Most frequent wind direction, to the nearest 10°, of the records within a bin around a
+    /// design value, ignoring calms
+    fn _prevailing_coincident_wind_direction(&self, values: &[f64], design: f64) -> f64 {
+        let mut counts = [0usize; 36];
+        for (idx, value) in values.iter().enumerate() {
+            let direction = self.wind_direction[idx];
+            if (value - design).abs() <= COINCIDENT_BIN_HALF_WIDTH
+                && self.wind_speed[idx] > 0.
+                && !direction.is_nan()
+            {
+                counts[(direction / 10.).round() as usize % 36] += 1;
+            }
+        }
+        match counts.iter().enumerate().max_by_key(|(_, count)| **count) {
+            Some((sector, count)) if *count > 0 => match sector {
+                0 => 360.,
+                _ => sector as f64 * 10.,
+            },
+            _ => f64::NAN,
+        }
+    }
+}
+
+impl EPWFile {
+    /// Compute the heating, cooling and extreme design conditions from the weather data
+    ///
+    /// ## Returns
+    /// The design conditions
+    pub fn compute_design_conditions(&self) -> DesignConditions {
+        self.data.compute_design_conditions(&self.header.location)
+    }
+
+    /// Replace the design conditions of the header with the ones computed from the weather data
+    pub fn update_design_conditions(&mut self) {
+        self.header.design_conditions = Some(self.compute_design_conditions());
+    }
+}
+
+/// Value below which a fraction of the values that are not NaN fall, interpolated linearly
+fn _percentile(values: &[f64], fraction: f64) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let position = fraction * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (position - below as f64) * (sorted[above] - sorted[below])
+}
+
+/// Mean of the coincident values of the records within a bin around a design value
+fn _mean_coincident(values: &[f64], coincident: &[f64], design: f64) -> f64 {
+    Statistics::from_values(
+        values
+            .iter()
+            .zip(coincident)
+            .filter(|(value, _)| (*value - design).abs() <= COINCIDENT_BIN_HALF_WIDTH)
+            .map(|(_, coincident)| coincident),
+    )
+    .mean
+}
+
+/// Humidity ratio in g/kg at a dew point temperature in °C and a pressure in Pascals
+fn _design_humidity_ratio(dew_point_temperature: f64, pressure: f64) -> f64 {
+    1000. * humidity_ratio(partial_vapor_pressure(dew_point_temperature), pressure)
+}
+
+/// Mean and sample standard deviation, NaN with fewer than two values
+fn _mean_and_sample_std_dev(values: &[f64]) -> (f64, f64) {
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let std_dev = match values.len() {
+        0 | 1 => f64::NAN,
+        _ => (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1.)).sqrt(),
+    };
+    (mean, std_dev)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_design_conditions() {
+        // A typical year is close to, but not the same as, the long term ASHRAE values
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let published = epw.header.design_conditions.take().unwrap();
+        epw.update_design_conditions();
+        let computed = epw.header.design_conditions.as_ref().unwrap();
+        assert_eq!(COMPUTED_DESIGN_CONDITIONS_SOURCE, computed.source);
+
+        let heating = computed.heating.as_ref().unwrap();
+        let expected = published.heating.unwrap();
+        assert_eq!(1, heating.coldest_month);
+        assert!(heating.dry_bulb_99_6 <= heating.dry_bulb_99);
+        assert!((expected.dry_bulb_99_6 - heating.dry_bulb_99_6).abs() < 2.);
+        assert!(
+            (expected.humidification_dew_point_99 - heating.humidification_dew_point_99).abs() < 2.
+        );
+        assert!(heating.coldest_month_wind_speed_0_4 >= heating.coldest_month_wind_speed_1);
+        assert!((0. ..=360.).contains(&heating.prevailing_coincident_wind_direction_99_6));
+
+        let cooling = computed.cooling.as_ref().unwrap();
+        let expected = published.cooling.unwrap();
+        assert!([7, 8].contains(&cooling.hottest_month));
+        assert!(
+            (expected.hottest_month_dry_bulb_range - cooling.hottest_month_dry_bulb_range).abs()
+                < 1.5
+        );
+        for (expected, actual) in [
+            (expected.dry_bulb_0_4, cooling.dry_bulb_0_4),
+            (
+                expected.mean_coincident_wet_bulb_0_4,
+                cooling.mean_coincident_wet_bulb_0_4,
+            ),
+            (expected.dry_bulb_2, cooling.dry_bulb_2),
+            (
+                expected.evaporation_wet_bulb_1,
+                cooling.evaporation_wet_bulb_1,
+            ),
+            (
+                expected.dehumidification_dew_point_1,
+                cooling.dehumidification_dew_point_1,
+            ),
+            (
+                expected.dehumidification_humidity_ratio_1,
+                cooling.dehumidification_humidity_ratio_1,
+            ),
+            (expected.enthalpy_1 / 10., cooling.enthalpy_1 / 10.),
+        ] {
+            assert!((expected - actual).abs() < 1.5, "{} {}", expected, actual);
+        }
+        assert!(
+            cooling.dry_bulb_0_4 >= cooling.dry_bulb_1 && cooling.dry_bulb_1 >= cooling.dry_bulb_2
+        );
+        assert!(cooling.hours_8_to_4_dry_bulb_12_8_to_20_6.unwrap() > 0.);
+
+        let extremes = computed.extremes.as_ref().unwrap();
+        assert!(extremes.wind_speed_1 >= extremes.wind_speed_5);
+        assert!(extremes.maximum_wet_bulb >= cooling.evaporation_wet_bulb_0_4);
+        assert_eq!(
+            Statistics::from_values(&epw.data.dry_bulb_temperature).maximum,
+            extremes.mean_maximum_dry_bulb
+        );
+        assert!(extremes.std_dev_maximum_dry_bulb.is_nan());
+        assert!(extremes.return_period_dry_bulb.is_empty());
+    }
+}
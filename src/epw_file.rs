@@ -7,12 +7,13 @@ parsing the specified file, or provided file content.
 */
 use crate::error::EPWParseError;
 use crate::header::parse_header;
+use crate::lines::{split_fields, EPWLines};
 use crate::weather_data::PresentWeather;
 use crate::{Header, WeatherData};
 use chrono::LocalResult::Single;
-use chrono::{FixedOffset, TimeZone};
+use chrono::{FixedOffset, TimeDelta, TimeZone};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Lines};
+use std::io::{BufRead, BufReader};
 
 /// EPWFile is the representation of the parsed file
 ///
@@ -36,7 +37,7 @@ impl EPWFile {
     /// An initialized EPWReader or an EPWParseError
     ///
     pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, EPWParseError> {
-        let mut lines = reader.lines();
+        let mut lines = EPWLines::new(reader).peekable();
         let header = parse_header(&mut lines)?;
         let data = _parse_data(&mut lines, &header)?;
 
@@ -45,6 +46,10 @@ impl EPWFile {
 
     /// Create an EPWFile instance from a file path
     ///
+    /// With the `compression` feature enabled, gzip compressed files and zip archives are
+    /// detected from their content and decompressed transparently. For zip archives the single
+    /// `.epw` member is read, use [EPWFile::from_zip_entry] to select a specific member.
+    ///
     /// ## Parameters
     /// - `path`: Path to file on the filesystem
     ///
@@ -57,12 +62,17 @@ impl EPWFile {
         };
 
         let reader = BufReader::new(f);
+
+        #[cfg(feature = "compression")]
+        return crate::compression::from_buffered_file(reader);
+
+        #[cfg(not(feature = "compression"))]
         Self::from_reader(reader)
     }
 }
 
-fn _parse_data<R: BufRead>(
-    lines: &mut Lines<R>,
+fn _parse_data<I: Iterator<Item = Result<String, EPWParseError>>>(
+    lines: &mut I,
     header: &Header,
 ) -> Result<WeatherData, EPWParseError> {
     // TODO: Don't panic
@@ -102,8 +112,16 @@ fn _parse_data<R: BufRead>(
     };
 
     for line in lines {
-        let line = line.expect("Unable to read line");
-        _parse_row(&line, &mut data, &header.location.time_zone)?
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        _parse_row(
+            &line,
+            &mut data,
+            &header.location.time_zone,
+            header.data_periods.records_per_hour,
+        )?
     }
 
     Ok(data)
@@ -113,8 +131,10 @@ fn _parse_row(
     line: &str,
     dest: &mut WeatherData,
     timezone: &FixedOffset,
+    records_per_hour: usize,
 ) -> Result<(), EPWParseError> {
-    let parts = line.split(",").collect::<Vec<&str>>();
+    let fields = split_fields(line);
+    let parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
     if parts.len() < 32 {
         return Err(EPWParseError::Data(format!("Invalid Data Row: {}", line)));
     }
@@ -156,7 +176,7 @@ fn _parse_row(
             )))
         }
     };
-    let minute = match parts[4].parse() {
+    let minute: u32 = match parts[4].parse() {
         Ok(val) => val,
         Err(e) => {
             return Err(EPWParseError::Data(format!(
@@ -166,17 +186,11 @@ fn _parse_row(
         }
     };
 
-    let timestamp = match timezone.with_ymd_and_hms(
-        year,
-        month,
-        day,
-        hour - 1,
-        match minute == 60 {
-            true => 0,
-            false => minute,
-        },
-        0,
-    ) {
+    // Timestamps mark the start of the interval each record covers. The minute field of an hourly
+    // file is not reliable (both 0 and 60 are common), so it is only used for sub-hourly data,
+    // where it marks the end of the interval. The last record of an hour may be written with
+    // minute 0 as well as 60.
+    let hour_start = match timezone.with_ymd_and_hms(year, month, day, hour - 1, 0, 0) {
         Single(val) => val,
         _ => {
             return Err(EPWParseError::Data(format!(
@@ -185,6 +199,14 @@ fn _parse_row(
             )))
         }
     };
+    let end_minute = match minute {
+        0 => 60,
+        _ => minute as i64,
+    };
+    let timestamp = match records_per_hour > 1 {
+        true => hour_start + TimeDelta::minutes(end_minute - 60 / records_per_hour as i64),
+        false => hour_start,
+    };
 
     let dry_bulb_temperature = _parse_float_value(parts[6], "Dry Bulb Temperature", 99.9)?;
     let dew_point_temperature = _parse_float_value(parts[7], "Dew Point Temperature", 99.9)?;
@@ -256,19 +278,24 @@ fn _parse_row(
     let snow_depth = _parse_float_value(parts[30], "Snow Depth", 999.)?;
     let days_since_last_snowfall = _parse_float_value(parts[31], "Days Since Last Snowfall", 99.)?;
 
-    let albedo = match parts.len() > 32 {
-        true => _parse_float_value(parts[32], "Albedo", 999.)?,
-        false => f64::NAN,
+    // The last three fields are optional and are often left blank
+    let albedo = match parts.get(32) {
+        Some(val) if !val.is_empty() => _parse_float_value(val, "Albedo", 999.)?,
+        _ => f64::NAN,
     };
 
-    let liquid_precipitation_depth = match parts.len() > 33 {
-        true => parts[33].parse().unwrap(),
-        false => f64::NAN,
+    let liquid_precipitation_depth = match parts.get(33) {
+        Some(val) if !val.is_empty() => {
+            _parse_float_value(val, "Liquid Precipitation Depth", 999.)?
+        }
+        _ => f64::NAN,
     };
 
-    let liquid_precipitation_quantity = match parts.len() > 34 {
-        true => parts[34].parse().unwrap(),
-        false => f64::NAN,
+    let liquid_precipitation_quantity = match parts.get(34) {
+        Some(val) if !val.is_empty() => {
+            _parse_float_value(val, "Liquid Precipitation Quantity", 99.)?
+        }
+        _ => f64::NAN,
     };
 
     dest.timestamp.push(timestamp);
@@ -317,6 +344,13 @@ fn _parse_row(
 }
 
 fn _parse_present_weather(condition_str: &str) -> Result<PresentWeather, EPWParseError> {
+    if condition_str.len() < 9 || !condition_str.is_ascii() {
+        return Err(EPWParseError::Data(format!(
+            "Invalid Conditions: {}",
+            condition_str
+        )));
+    }
+
     let thunderstorm = match condition_str[0..1].parse() {
         Ok(val) => val,
         Err(e) => {
@@ -435,3 +469,110 @@ fn _parse_float_value(value: &str, name: &str, missing_value: f64) -> Result<f64
     };
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+    use std::fs;
+
+    const TEST_FILE: &str = "./data/USA_FL_Tampa_TMY2.epw";
+
+    /// The header and the first day of data from the test file
+    fn _test_lines() -> Vec<String> {
+        let content = fs::read_to_string(TEST_FILE).unwrap();
+        content.lines().take(32).map(String::from).collect()
+    }
+
+    fn _assert_matches_test_file(epw: &EPWFile) {
+        let expected = EPWFile::from_reader(_test_lines().join("\n").as_bytes()).unwrap();
+        assert_eq!(
+            expected.header.location.latitude,
+            epw.header.location.latitude
+        );
+        assert_eq!(
+            expected.header.ground_temperatures,
+            epw.header.ground_temperatures
+        );
+        assert_eq!(expected.data.timestamp, epw.data.timestamp);
+        assert_eq!(
+            expected.data.dry_bulb_temperature,
+            epw.data.dry_bulb_temperature
+        );
+        assert_eq!(
+            expected.data.days_since_last_snowfall,
+            epw.data.days_since_last_snowfall
+        );
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        let content = format!("\u{feff}{}", _test_lines().join("\n"));
+        let epw = EPWFile::from_reader(content.as_bytes()).unwrap();
+        assert_eq!("TAMPA", epw.header.location.city);
+        _assert_matches_test_file(&epw);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let content = format!("{}\r\n", _test_lines().join("\r\n"));
+        let epw = EPWFile::from_reader(content.as_bytes()).unwrap();
+        assert_eq!(24, epw.data.timestamp.len());
+        _assert_matches_test_file(&epw);
+    }
+
+    #[test]
+    fn test_whitespace_around_values() {
+        let lines: Vec<String> = _test_lines()
+            .iter()
+            .map(|line| format!("{}  ", line.replace(',', " , ")))
+            .collect();
+        let epw = EPWFile::from_reader(lines.join("\n").as_bytes()).unwrap();
+        assert_eq!("TAMPA", epw.header.location.city);
+        _assert_matches_test_file(&epw);
+    }
+
+    #[test]
+    fn test_quoted_fields() {
+        let mut lines = _test_lines();
+        lines[0] = lines[0].replace("TAMPA", "\"Tampa, Bay Area\"");
+        let epw = EPWFile::from_reader(lines.join("\n").as_bytes()).unwrap();
+        assert_eq!("Tampa, Bay Area", epw.header.location.city);
+        assert_eq!("FL", epw.header.location.state_province_region);
+        _assert_matches_test_file(&epw);
+    }
+
+    #[test]
+    fn test_latin_1_city_name() {
+        let lines = _test_lines();
+        // 0xE3 is "ã" in Latin-1, and is not valid UTF-8 on its own
+        let mut content = b"LOCATION,S\xe3o Paulo".to_vec();
+        content.extend(&lines[0].as_bytes()["LOCATION,TAMPA".len()..]);
+        content.extend(format!("\n{}", lines[1..].join("\n")).as_bytes());
+
+        let epw = EPWFile::from_reader(content.as_slice()).unwrap();
+        assert_eq!("São Paulo", epw.header.location.city);
+        _assert_matches_test_file(&epw);
+    }
+
+    #[test]
+    fn test_sub_hourly_timestamps() {
+        let lines = _test_lines();
+        for last_minute in ["60", "0"] {
+            let mut content: Vec<String> = lines[..8].to_vec();
+            content[7] = content[7].replace("DATA PERIODS,1,1,", "DATA PERIODS,1,4,");
+            for line in &lines[8..10] {
+                for minute in ["15", "30", "45", last_minute] {
+                    content.push(line.replacen(",60,", &format!(",{},", minute), 1));
+                }
+            }
+            let epw = EPWFile::from_reader(content.join("\n").as_bytes()).unwrap();
+
+            let first = epw.data.timestamp[0];
+            assert_eq!((1, 0, 0), (first.day(), first.hour(), first.minute()));
+            for (idx, timestamp) in epw.data.timestamp.iter().enumerate() {
+                assert_eq!(first + TimeDelta::minutes(15 * idx as i64), *timestamp);
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #[derive(Debug)]
 pub enum EPWParseError {
     FileNotFound(String),
+    Io(String),
     UnexpectedData(String),
     Location(String),
     GroundTemperature(String),
@@ -9,4 +10,7 @@ pub enum EPWParseError {
     TypicalExtremePeriods(String),
     DesignConditions(String),
     Data(String),
+    DesignDay(String),
+    Stat(String),
+    Compression(String),
 }
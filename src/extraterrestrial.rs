@@ -0,0 +1,316 @@
+/*!
+Extraterrestrial radiation computed from the solar geometry.
+
+Converted files sometimes carry zeros or missing values in the extraterrestrial radiation fields.
+[WeatherData::extraterrestrial_radiation] recomputes them from the solar constant, the
+Earth-Sun distance for the day of year and the solar geometry integrated analytically over each
+record's interval, following the TMY2/TMY3 conventions:
+- the horizontal radiation is the mean of `I0 cos(zenith)` over the interval, counting only the
+  time the sun is above the horizon
+- the direct normal radiation is `I0` scaled by the fraction of the interval the sun is above the
+  horizon
+
+where `I0` is the extraterrestrial normal irradiance for the day. The values are mean
+irradiances over the interval, which for hourly data are the Wh/m² of the file. The TMY2 files
+approximate the hours containing sunrise and sunset more coarsely, so their horizontal values for
+those hours can be a few tens of Wh/m² higher than the recomputed ones.
+
+[ExtraterrestrialReport] compares the recomputed values to those in the file. A file whose
+timestamps are shifted, e.g. by a wrong time zone or an interval-starting hour convention, matches
+the recomputed values best at a non-zero [ExtraterrestrialReport::time_shift].
+
+*/
+use crate::header::Location;
+use crate::solar::{solar_position, SOLAR_CONSTANT};
+use crate::{EPWFile, WeatherData};
+use chrono::{Datelike, TimeDelta};
+use std::f64::consts::PI;
+
+/// Largest time shift in hours tried by [WeatherData::compare_extraterrestrial_radiation]
+const MAX_TIME_SHIFT_HOURS: i64 = 3;
+
+/// Smallest recomputed horizontal radiation for which a zero in the file is counted as missing,
+/// the resolution of the EPW format
+const MISSING_THRESHOLD: f64 = 1.;
+
+/// Extraterrestrial radiation for each record
+#[derive(Debug)]
+pub struct ExtraterrestrialRadiation {
+    pub horizontal: Vec<f64>,
+    pub direct_normal: Vec<f64>,
+}
+
+/// Comparison of the extraterrestrial radiation in a file with recomputed values
+#[derive(Debug)]
+pub struct ExtraterrestrialReport {
+    /// Number of records where the file has an extraterrestrial horizontal radiation value
+    pub compared_records: usize,
+    /// Number of records where the file value is missing or zero while the recomputed value is at
+    /// least 1 Wh/m²
+    pub missing_records: usize,
+    /// Mean absolute difference of the horizontal radiation, over the compared records
+    pub mean_absolute_difference_horizontal: f64,
+    /// Mean absolute difference of the direct normal radiation, over the compared records
+    pub mean_absolute_difference_direct_normal: f64,
+    /// Shift that best aligns the file with the recomputed values: the file value at `t` matches
+    /// the recomputed value at `t + time_shift`. Zero for a correctly aligned file.
+    pub time_shift: TimeDelta,
+    /// Mean absolute difference of the horizontal radiation at the best time shift
+    pub shifted_mean_absolute_difference_horizontal: f64,
+}
+
+/// Extraterrestrial normal irradiance in W/m² for a day of the year (Spencer, 1971)
+pub fn extraterrestrial_normal_irradiance(day_of_year: u32) -> f64 {
+    let gamma = 2. * PI * (day_of_year as f64 - 1.) / 365.;
+    SOLAR_CONSTANT
+        * (1.000110
+            + 0.034221 * gamma.cos()
+            + 0.001280 * gamma.sin()
+            + 0.000719 * (2. * gamma).cos()
+            + 0.000077 * (2. * gamma).sin())
+}
+
+impl WeatherData {
+    /// Compute the extraterrestrial radiation for each record
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// The extraterrestrial horizontal and direct normal radiation for each record
+    pub fn extraterrestrial_radiation(
+        &self,
+        location: &Location,
+        records_per_hour: usize,
+    ) -> ExtraterrestrialRadiation {
+        let interval = TimeDelta::seconds(3600 / records_per_hour.max(1) as i64);
+        let phi = location.latitude.to_radians();
+
+        let mut radiation = ExtraterrestrialRadiation {
+            horizontal: Vec::with_capacity(self.timestamp.len()),
+            direct_normal: Vec::with_capacity(self.timestamp.len()),
+        };
+        for timestamp in &self.timestamp {
+            let start = timestamp.with_timezone(&location.time_zone);
+            let middle =
+                solar_position(location.latitude, location.longitude, start + interval / 2);
+            let start_angle =
+                solar_position(location.latitude, location.longitude, start).hour_angle;
+            let mut end_angle =
+                solar_position(location.latitude, location.longitude, start + interval).hour_angle;
+            if end_angle < start_angle {
+                end_angle += 360.;
+            }
+
+            let delta = middle.declination.to_radians();
+            let sunset_angle = (-phi.tan() * delta.tan()).clamp(-1., 1.).acos();
+            let (start_angle, end_angle) = (start_angle.to_radians(), end_angle.to_radians());
+
+            // Integrate cos(zenith) over the parts of the interval where the sun is up, the day
+            // may be centered on a hour angle of 0 or 360° when the interval crosses midnight
+            let mut sunlit = 0.;
+            let mut integral = 0.;
+            for center in [-2. * PI, 0., 2. * PI] {
+                let low = start_angle.max(center - sunset_angle);
+                let high = end_angle.min(center + sunset_angle);
+                if high > low {
+                    sunlit += high - low;
+                    integral += (high - low) * phi.sin() * delta.sin()
+                        + phi.cos() * delta.cos() * ((high - center).sin() - (low - center).sin());
+                }
+            }
+
+            let width = end_angle - start_angle;
+            let irradiance = extraterrestrial_normal_irradiance(start.ordinal());
+            radiation
+                .horizontal
+                .push((irradiance * integral / width).max(0.));
+            radiation.direct_normal.push(irradiance * sunlit / width);
+        }
+        radiation
+    }
+
+    /// Compare the extraterrestrial radiation in the file with recomputed values
+    ///
+    /// ## Parameters
+    /// - `computed`: Recomputed values, see [WeatherData::extraterrestrial_radiation]
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// A report of the differences
+    pub fn compare_extraterrestrial_radiation(
+        &self,
+        computed: &ExtraterrestrialRadiation,
+        records_per_hour: usize,
+    ) -> ExtraterrestrialReport {
+        let file_horizontal = &self.extraterrestrial_horizontal_radiation;
+        let file_normal = &self.extraterrestrial_direct_normal_radiation;
+
+        let missing_records = (0..file_horizontal.len())
+            .filter(|idx| {
+                computed.horizontal[*idx] >= MISSING_THRESHOLD
+                    && (file_horizontal[*idx].is_nan() || file_horizontal[*idx] == 0.)
+            })
+            .count();
+        let compared_records = file_horizontal.iter().filter(|val| !val.is_nan()).count();
+
+        let records_per_hour = records_per_hour.max(1) as i64;
+        let mut time_shift = 0;
+        let mut shifted_difference = f64::INFINITY;
+        for shift in
+            -MAX_TIME_SHIFT_HOURS * records_per_hour..=MAX_TIME_SHIFT_HOURS * records_per_hour
+        {
+            let difference =
+                _mean_absolute_difference(file_horizontal, &computed.horizontal, shift);
+            if difference < shifted_difference {
+                time_shift = shift;
+                shifted_difference = difference;
+            }
+        }
+
+        ExtraterrestrialReport {
+            compared_records,
+            missing_records,
+            mean_absolute_difference_horizontal: _mean_absolute_difference(
+                file_horizontal,
+                &computed.horizontal,
+                0,
+            ),
+            mean_absolute_difference_direct_normal: _mean_absolute_difference(
+                file_normal,
+                &computed.direct_normal,
+                0,
+            ),
+            time_shift: TimeDelta::seconds(time_shift * 3600 / records_per_hour),
+            shifted_mean_absolute_difference_horizontal: shifted_difference,
+        }
+    }
+
+    /// Replace the extraterrestrial radiation columns with recomputed values
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// A report comparing the replaced values to the recomputed ones
+    pub fn recompute_extraterrestrial_radiation(
+        &mut self,
+        location: &Location,
+        records_per_hour: usize,
+    ) -> ExtraterrestrialReport {
+        let computed = self.extraterrestrial_radiation(location, records_per_hour);
+        let report = self.compare_extraterrestrial_radiation(&computed, records_per_hour);
+        self.extraterrestrial_horizontal_radiation = computed.horizontal;
+        self.extraterrestrial_direct_normal_radiation = computed.direct_normal;
+        report
+    }
+}
+
+impl EPWFile {
+    /// Compute the extraterrestrial radiation for each record
+    ///
+    /// ## Returns
+    /// The extraterrestrial horizontal and direct normal radiation for each record
+    pub fn extraterrestrial_radiation(&self) -> ExtraterrestrialRadiation {
+        self.data.extraterrestrial_radiation(
+            &self.header.location,
+            self.header.data_periods.records_per_hour,
+        )
+    }
+
+    /// Compare the extraterrestrial radiation in the file with recomputed values
+    ///
+    /// ## Returns
+    /// A report of the differences
+    pub fn compare_extraterrestrial_radiation(&self) -> ExtraterrestrialReport {
+        self.data.compare_extraterrestrial_radiation(
+            &self.extraterrestrial_radiation(),
+            self.header.data_periods.records_per_hour,
+        )
+    }
+
+    /// Replace the extraterrestrial radiation columns with recomputed values
+    ///
+    /// ## Returns
+    /// A report comparing the replaced values to the recomputed ones
+    pub fn recompute_extraterrestrial_radiation(&mut self) -> ExtraterrestrialReport {
+        self.data.recompute_extraterrestrial_radiation(
+            &self.header.location,
+            self.header.data_periods.records_per_hour,
+        )
+    }
+}
+
+/// Mean absolute difference between `file[idx]` and `computed[idx + shift]`, skipping missing
+/// values
+fn _mean_absolute_difference(file: &[f64], computed: &[f64], shift: i64) -> f64 {
+    let mut total = 0.;
+    let mut count = 0;
+    for (idx, value) in file.iter().enumerate() {
+        let shifted = idx as i64 + shift;
+        if value.is_nan() || shifted < 0 || shifted >= computed.len() as i64 {
+            continue;
+        }
+        total += (value - computed[shifted as usize]).abs();
+        count += 1;
+    }
+    match count > 0 {
+        true => total / count as f64,
+        false => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extraterrestrial_radiation() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let computed = epw.extraterrestrial_radiation();
+
+        // TMY2 uses a coarser approximation for the hours containing sunrise or sunset, and
+        // corrects the sun position for refraction, so only compare the hours where the sun is up
+        // throughout
+        for idx in 0..48 {
+            if computed.horizontal[idx] < 200. {
+                continue;
+            }
+            let difference =
+                epw.data.extraterrestrial_horizontal_radiation[idx] - computed.horizontal[idx];
+            assert!(
+                difference.abs() < 0.02 * computed.horizontal[idx],
+                "{} {}",
+                idx,
+                difference
+            );
+            let difference = epw.data.extraterrestrial_direct_normal_radiation[idx]
+                - computed.direct_normal[idx];
+            assert!(difference.abs() < 1., "{} {}", idx, difference);
+        }
+
+        let report = epw.compare_extraterrestrial_radiation();
+        assert_eq!(8760, report.compared_records);
+        assert_eq!(0, report.missing_records);
+        assert_eq!(TimeDelta::zero(), report.time_shift);
+        assert!(report.mean_absolute_difference_horizontal < 5.);
+
+        // Values an hour late are detected
+        let original = epw.data.extraterrestrial_horizontal_radiation.clone();
+        epw.data
+            .extraterrestrial_horizontal_radiation
+            .rotate_right(1);
+        let report = epw.compare_extraterrestrial_radiation();
+        assert_eq!(TimeDelta::hours(-1), report.time_shift);
+
+        epw.data.extraterrestrial_horizontal_radiation = vec![0.; original.len()];
+        let report = epw.recompute_extraterrestrial_radiation();
+        assert!(report.missing_records > 4000);
+        assert_eq!(
+            computed.horizontal,
+            epw.data.extraterrestrial_horizontal_radiation
+        );
+    }
+}
@@ -0,0 +1,443 @@
+/*!
+Filling of the missing (NaN) values of the weather data.
+
+A gap is a run of consecutive missing values in a numeric field. Fields without any value, such as
+the albedo of files that don't have it, have no gaps. Each gap is filled with the strategy of its
+field:
+- Extraterrestrial radiation: recomputed from the position of the sun by [EPWFile::fill_gaps],
+  which knows the location ([GapFillMethod::Recomputed]), whatever the length of the gap.
+  [WeatherData::fill_gaps] treats these fields like the other fields.
+- Radiation, illuminance and zenith luminance: a clear sky profile scaled to the values around the
+  gap ([GapFillMethod::ClearSkyScaled]). The profile is the Haurwitz global horizontal irradiance
+  for the horizontal fields and the Meinel direct normal irradiance for the direct normal fields,
+  and the scale is the ratio of the known values to the profile over the day on each side of the
+  gap. Records where the sun is down are filled with 0.
+- Wind direction: interpolation along the shortest arc between the values on each side of short
+  gaps ([GapFillMethod::CircularInterpolation])
+- Other fields: linear interpolation between the values on each side of short gaps
+  ([GapFillMethod::LinearInterpolation])
+
+Long gaps, gaps at the start or end of the data, and radiation gaps without known daytime values
+around them are filled with the mean of the nearest known values at the same time of day before
+and after the gap ([GapFillMethod::AdjacentDays]), a vector mean for the wind direction.
+
+Gaps longer than [GapFillOptions::max_gap_hours], or that no strategy can fill, are left missing.
+Every gap is listed in the [GapFillReport], with the strategy that filled it.
+
+*/
+use crate::solar::{relative_air_mass, SolarPosition, SOLAR_CONSTANT};
+use crate::{EPWFile, WeatherData};
+use chrono::{DateTime, FixedOffset};
+
+const DIRECT_NORMAL_FIELDS: [&str; 2] = ["direct_normal_radiation", "direct_normal_illuminance"];
+
+const HORIZONTAL_SOLAR_FIELDS: [&str; 5] = [
+    "global_horizontal_radiation",
+    "diffuse_horizontal_radiation",
+    "global_horizontal_illuminance",
+    "diffuse_horizontal_illuminance",
+    "zenith_luminance",
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GapFillMethod {
+    LinearInterpolation,
+    CircularInterpolation,
+    AdjacentDays,
+    ClearSkyScaled,
+    /// Computed exactly, used for the extraterrestrial radiation
+    Recomputed,
+}
+
+/// Options of [WeatherData::fill_gaps]
+#[derive(Debug, PartialEq, Clone)]
+pub struct GapFillOptions {
+    /// Longest gap in hours filled by interpolation, longer gaps use the adjacent days
+    pub max_interpolation_hours: usize,
+    /// Longest gap in hours that is filled, `None` to fill gaps of any length
+    pub max_gap_hours: Option<usize>,
+}
+
+impl Default for GapFillOptions {
+    /// Interpolate gaps of up to 6 hours, and fill gaps of any length
+    fn default() -> Self {
+        Self {
+            max_interpolation_hours: 6,
+            max_gap_hours: None,
+        }
+    }
+}
+
+/// A run of consecutive missing values in a field
+#[derive(Debug, PartialEq)]
+pub struct Gap {
+    /// Field name of the column in [WeatherData]
+    pub field: &'static str,
+    /// Index of the first missing record
+    pub start: usize,
+    /// Number of missing records
+    pub length: usize,
+    /// Timestamp of the first missing record
+    pub timestamp: DateTime<FixedOffset>,
+}
+
+/// A gap and the strategy that filled it
+#[derive(Debug, PartialEq)]
+pub struct FilledGap {
+    pub gap: Gap,
+    pub method: GapFillMethod,
+}
+
+/// The gaps found by [WeatherData::fill_gaps]
+#[derive(Debug, Default)]
+pub struct GapFillReport {
+    pub filled: Vec<FilledGap>,
+    /// Gaps that were too long or that no strategy could fill
+    pub unfilled: Vec<Gap>,
+}
+
+impl WeatherData {
+    /// Fill the missing values of the numeric fields
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    /// - `options`: Gap lengths for each strategy
+    ///
+    /// ## Returns
+    /// A report of every gap and how it was filled
+    pub fn fill_gaps(
+        &mut self,
+        solar_position: &[SolarPosition],
+        records_per_hour: usize,
+        options: &GapFillOptions,
+    ) -> GapFillReport {
+        let records_per_hour = records_per_hour.max(1);
+        let records_per_day = 24 * records_per_hour;
+        let max_interpolation = options.max_interpolation_hours * records_per_hour;
+        let max_gap = options.max_gap_hours.map(|hours| hours * records_per_hour);
+
+        let horizontal_profile: Vec<f64> = solar_position
+            .iter()
+            .map(|sun| {
+                let cos_zenith = sun.zenith.to_radians().cos();
+                match sun.is_up() {
+                    true => 1098. * cos_zenith * (-0.057 / cos_zenith).exp(),
+                    false => 0.,
+                }
+            })
+            .collect();
+        let direct_normal_profile: Vec<f64> = solar_position
+            .iter()
+            .map(|sun| match sun.is_up() {
+                true => SOLAR_CONSTANT * 0.7_f64.powf(relative_air_mass(sun.zenith).powf(0.678)),
+                false => 0.,
+            })
+            .collect();
+
+        let timestamps = self.timestamp.clone();
+        let mut report = GapFillReport::default();
+        for (field, values) in self.numeric_columns_mut() {
+            if values.iter().all(|v| v.is_nan()) {
+                continue;
+            }
+            let profile = if HORIZONTAL_SOLAR_FIELDS.contains(&field) {
+                Some(&horizontal_profile)
+            } else if DIRECT_NORMAL_FIELDS.contains(&field) {
+                Some(&direct_normal_profile)
+            } else {
+                None
+            };
+            let circular = field == "wind_direction";
+
+            let original = values.clone();
+            for (start, length) in _find_gaps(&original) {
+                let gap = Gap {
+                    field,
+                    start,
+                    length,
+                    timestamp: timestamps[start],
+                };
+                if max_gap.is_some_and(|max| length > max) {
+                    report.unfilled.push(gap);
+                    continue;
+                }
+
+                let end = start + length;
+                let bounded = start > 0 && end < original.len();
+                let filled = match profile {
+                    Some(profile) => {
+                        _clear_sky_scaled(&original, profile, start, length, records_per_day)
+                            .map(|filled| (GapFillMethod::ClearSkyScaled, filled))
+                    }
+                    None if bounded && length <= max_interpolation => {
+                        let method = match circular {
+                            true => GapFillMethod::CircularInterpolation,
+                            false => GapFillMethod::LinearInterpolation,
+                        };
+                        Some((
+                            method,
+                            _interpolate(original[start - 1], original[end], length, circular),
+                        ))
+                    }
+                    None => None,
+                }
+                .or_else(|| {
+                    _adjacent_days(&original, start, length, records_per_day, circular)
+                        .map(|filled| (GapFillMethod::AdjacentDays, filled))
+                });
+
+                match filled {
+                    Some((method, filled)) => {
+                        values[start..end].copy_from_slice(&filled);
+                        report.filled.push(FilledGap { gap, method });
+                    }
+                    None => report.unfilled.push(gap),
+                }
+            }
+        }
+        report
+    }
+}
+
+impl EPWFile {
+    /// Fill the missing values of the numeric fields
+    ///
+    /// ## Parameters
+    /// - `options`: Gap lengths for each strategy
+    ///
+    /// ## Returns
+    /// A report of every gap and how it was filled
+    pub fn fill_gaps(&mut self, options: &GapFillOptions) -> GapFillReport {
+        let records_per_hour = self.header.data_periods.records_per_hour;
+        let mut filled: Vec<FilledGap> = Vec::new();
+        let data = &mut self.data;
+        if data
+            .extraterrestrial_horizontal_radiation
+            .iter()
+            .chain(&data.extraterrestrial_direct_normal_radiation)
+            .any(|v| v.is_nan())
+        {
+            let computed = data.extraterrestrial_radiation(&self.header.location, records_per_hour);
+            for (field, values, computed) in [
+                (
+                    "extraterrestrial_horizontal_radiation",
+                    &mut data.extraterrestrial_horizontal_radiation,
+                    computed.horizontal,
+                ),
+                (
+                    "extraterrestrial_direct_normal_radiation",
+                    &mut data.extraterrestrial_direct_normal_radiation,
+                    computed.direct_normal,
+                ),
+            ] {
+                for (start, length) in _find_gaps(values) {
+                    values[start..start + length].copy_from_slice(&computed[start..start + length]);
+                    filled.push(FilledGap {
+                        gap: Gap {
+                            field,
+                            start,
+                            length,
+                            timestamp: data.timestamp[start],
+                        },
+                        method: GapFillMethod::Recomputed,
+                    });
+                }
+            }
+        }
+
+        let solar_position = self.solar_position();
+        let mut report = self
+            .data
+            .fill_gaps(&solar_position, records_per_hour, options);
+        filled.append(&mut report.filled);
+        report.filled = filled;
+        report
+    }
+}
+
+/// Start and length of each run of NaN values
+fn _find_gaps(values: &[f64]) -> Vec<(usize, usize)> {
+    let mut gaps: Vec<(usize, usize)> = Vec::new();
+    for (idx, value) in values.iter().enumerate() {
+        if !value.is_nan() {
+            continue;
+        }
+        match gaps.last_mut() {
+            Some((start, length)) if *start + *length == idx => *length += 1,
+            _ => gaps.push((idx, 1)),
+        }
+    }
+    gaps
+}
+
+/// Values of a gap interpolated between the values before and after it
+fn _interpolate(before: f64, after: f64, length: usize, circular: bool) -> Vec<f64> {
+    let difference = match circular {
+        true => (after - before + 180.).rem_euclid(360.) - 180.,
+        false => after - before,
+    };
+    (1..=length)
+        .map(|k| {
+            let value = before + difference * k as f64 / (length + 1) as f64;
+            match circular {
+                true => value.rem_euclid(360.),
+                false => value,
+            }
+        })
+        .collect()
+}
+
+/// Values of a gap from the clear sky profile, scaled to the known values of the day on each side
+fn _clear_sky_scaled(
+    values: &[f64],
+    profile: &[f64],
+    start: usize,
+    length: usize,
+    records_per_day: usize,
+) -> Option<Vec<f64>> {
+    let gap = start..start + length;
+    if profile[gap.clone()].iter().all(|p| *p <= 0.) {
+        return Some(vec![0.; length]);
+    }
+
+    let window =
+        start.saturating_sub(records_per_day)..(gap.end + records_per_day).min(values.len());
+    let (known, reference) = window
+        .filter(|idx| !values[*idx].is_nan() && profile[*idx] > 0.)
+        .fold((0., 0.), |(known, reference), idx| {
+            (known + values[idx], reference + profile[idx])
+        });
+    match reference > 0. {
+        true => Some(profile[gap].iter().map(|p| p * known / reference).collect()),
+        false => None,
+    }
+}
+
+/// Values of a gap from the nearest known values at the same time of day before and after it
+fn _adjacent_days(
+    values: &[f64],
+    start: usize,
+    length: usize,
+    records_per_day: usize,
+    circular: bool,
+) -> Option<Vec<f64>> {
+    (start..start + length)
+        .map(|idx| {
+            let before = (1..=idx / records_per_day)
+                .map(|days| values[idx - days * records_per_day])
+                .find(|v| !v.is_nan());
+            let after = (1..)
+                .map(|days| idx + days * records_per_day)
+                .take_while(|other| *other < values.len())
+                .map(|other| values[other])
+                .find(|v| !v.is_nan());
+            let neighbours: Vec<f64> = before.into_iter().chain(after).collect();
+            match (neighbours.is_empty(), circular) {
+                (true, _) => None,
+                (false, true) => {
+                    let east: f64 = neighbours.iter().map(|d| d.to_radians().sin()).sum();
+                    let north: f64 = neighbours.iter().map(|d| d.to_radians().cos()).sum();
+                    Some(east.atan2(north).to_degrees().rem_euclid(360.))
+                }
+                (false, false) => Some(neighbours.iter().sum::<f64>() / neighbours.len() as f64),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_gaps() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        // The file's extraterrestrial radiation was computed with a different algorithm
+        epw.recompute_extraterrestrial_radiation();
+        let original = epw.data.clone();
+        for idx in 100..103 {
+            epw.data.dry_bulb_temperature[idx] = f64::NAN;
+        }
+        for idx in 200..230 {
+            epw.data.relative_humidity[idx] = f64::NAN;
+        }
+        epw.data.wind_direction[300] = f64::NAN;
+        epw.data.wind_direction[299] = 350.;
+        epw.data.wind_direction[301] = 10.;
+        for idx in 4356..4362 {
+            epw.data.global_horizontal_radiation[idx] = f64::NAN;
+        }
+
+        for idx in 4350..4360 {
+            epw.data.extraterrestrial_horizontal_radiation[idx] = f64::NAN;
+        }
+        epw.data.extraterrestrial_direct_normal_radiation[4356] = f64::NAN;
+
+        let mut report = epw.fill_gaps(&GapFillOptions::default());
+        assert!(report.unfilled.is_empty());
+        assert_eq!(6, report.filled.len());
+
+        // The extraterrestrial radiation is recomputed exactly
+        let extraterrestrial: Vec<FilledGap> = report.filled.drain(..2).collect();
+        assert_eq!(GapFillMethod::Recomputed, extraterrestrial[0].method);
+        assert_eq!(
+            (4350, 10),
+            (
+                extraterrestrial[0].gap.start,
+                extraterrestrial[0].gap.length
+            )
+        );
+        assert_eq!(
+            "extraterrestrial_direct_normal_radiation",
+            extraterrestrial[1].gap.field
+        );
+        assert_eq!(
+            original.extraterrestrial_horizontal_radiation,
+            epw.data.extraterrestrial_horizontal_radiation
+        );
+        assert_eq!(
+            original.extraterrestrial_direct_normal_radiation,
+            epw.data.extraterrestrial_direct_normal_radiation
+        );
+
+        let temperature = &report.filled[0];
+        assert_eq!("dry_bulb_temperature", temperature.gap.field);
+        assert_eq!((100, 3), (temperature.gap.start, temperature.gap.length));
+        assert_eq!(GapFillMethod::LinearInterpolation, temperature.method);
+        let step = (original.dry_bulb_temperature[103] - original.dry_bulb_temperature[99]) / 4.;
+        let expected = original.dry_bulb_temperature[99] + step;
+        assert!((expected - epw.data.dry_bulb_temperature[100]).abs() < 1e-9);
+
+        let humidity = &report.filled[1];
+        assert_eq!(GapFillMethod::AdjacentDays, humidity.method);
+        let expected = (original.relative_humidity[176] + original.relative_humidity[248]) / 2.;
+        assert!((expected - epw.data.relative_humidity[200]).abs() < 1e-9);
+
+        let radiation = &report.filled[2];
+        assert_eq!(GapFillMethod::ClearSkyScaled, radiation.method);
+        // An afternoon of July 1st, the shape follows the sun and the total is close
+        let filled = &epw.data.global_horizontal_radiation[4356..4362];
+        assert!(filled.windows(2).all(|pair| pair[0] > pair[1]));
+        let expected: f64 = original.global_horizontal_radiation[4356..4362]
+            .iter()
+            .sum();
+        assert!((expected - filled.iter().sum::<f64>()).abs() < 0.25 * expected);
+
+        let direction = &report.filled[3];
+        assert_eq!(GapFillMethod::CircularInterpolation, direction.method);
+        assert!(epw.data.wind_direction[300].abs() < 1e-9);
+
+        for idx in 200..230 {
+            epw.data.relative_humidity[idx] = f64::NAN;
+        }
+        let options = GapFillOptions {
+            max_gap_hours: Some(24),
+            ..GapFillOptions::default()
+        };
+        let report = epw.fill_gaps(&options);
+        assert!(report.filled.is_empty());
+        assert_eq!(30, report.unfilled[0].length);
+        assert!(epw.data.relative_humidity[200].is_nan());
+    }
+}
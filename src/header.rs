@@ -1,11 +1,14 @@
 use crate::error::EPWParseError;
+use crate::lines::split_fields;
 use chrono::FixedOffset;
-use std::collections::VecDeque;
 use std::fmt;
-use std::io::{BufRead, Lines};
+use std::iter::Peekable;
 
 const LOCATION_KEY: &str = "LOCATION";
 const DESIGN_CONDITIONS_KEY: &str = "DESIGN CONDITIONS";
+const HEATING_KEY: &str = "Heating";
+const COOLING_KEY: &str = "Cooling";
+const EXTREMES_KEY: &str = "Extremes";
 const TYPICAL_EXTREME_PERIODS_KEY: &str = "TYPICAL/EXTREME PERIODS";
 
 const GROUND_TEMPERATURES_KEY: &str = "GROUND TEMPERATURES";
@@ -80,6 +83,18 @@ pub struct HolidayDaylightSavings {
     pub holidays: Vec<Holiday>,
 }
 
+impl Default for HolidayDaylightSavings {
+    /// No leap year, daylight savings or holidays
+    fn default() -> Self {
+        Self {
+            leap_year: false,
+            daylight_savings_start: "0".to_string(),
+            daylight_savings_end: "0".to_string(),
+            holidays: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DataPeriod {
     pub name: String,
@@ -108,30 +123,186 @@ pub struct DataPeriods {
     pub periods: Vec<DataPeriod>,
 }
 
+impl Default for DataPeriods {
+    /// A single hourly period covering the whole year
+    fn default() -> Self {
+        Self {
+            records_per_hour: 1,
+            periods: vec![DataPeriod {
+                name: "Data".to_string(),
+                start_day_of_week: DayOfWeek::Sunday,
+                start_day: "1/1".to_string(),
+                end_day: "12/31".to_string(),
+            }],
+        }
+    }
+}
+
+/// Heating design conditions from the ASHRAE Handbook of Fundamentals
+///
+/// Temperatures are in °C, humidity ratios in g/kg, wind speeds in m/s and wind directions in
+/// degrees. Percentiles are annual cumulative frequencies of occurrence, e.g. the `99_6` values are
+/// exceeded 99.6% of the year. Values that are blank in the file are NaN.
+#[derive(Debug, PartialEq)]
+pub struct HeatingDesignConditions {
+    pub coldest_month: u32,
+    pub dry_bulb_99_6: f64,
+    pub dry_bulb_99: f64,
+    pub humidification_dew_point_99_6: f64,
+    pub humidification_humidity_ratio_99_6: f64,
+    pub humidification_mean_coincident_dry_bulb_99_6: f64,
+    pub humidification_dew_point_99: f64,
+    pub humidification_humidity_ratio_99: f64,
+    pub humidification_mean_coincident_dry_bulb_99: f64,
+    /// Extreme wind speed of the coldest month, 0.4% cumulative frequency
+    pub coldest_month_wind_speed_0_4: f64,
+    pub coldest_month_mean_coincident_dry_bulb_0_4: f64,
+    /// Extreme wind speed of the coldest month, 1% cumulative frequency
+    pub coldest_month_wind_speed_1: f64,
+    pub coldest_month_mean_coincident_dry_bulb_1: f64,
+    /// Mean wind speed coincident with the 99.6% dry bulb temperature
+    pub mean_coincident_wind_speed_99_6: f64,
+    /// Prevailing wind direction coincident with the 99.6% dry bulb temperature
+    pub prevailing_coincident_wind_direction_99_6: f64,
+}
+
+/// Cooling, evaporation, dehumidification and enthalpy design conditions from the ASHRAE Handbook
+/// of Fundamentals
+///
+/// Temperatures are in °C, humidity ratios in g/kg, enthalpies in kJ/kg, wind speeds in m/s and
+/// wind directions in degrees. Percentiles are annual cumulative frequencies of occurrence, e.g.
+/// the `0_4` values are exceeded 0.4% of the year. Values that are blank in the file are NaN.
+#[derive(Debug, PartialEq)]
+pub struct CoolingDesignConditions {
+    pub hottest_month: u32,
+    /// Mean daily dry bulb temperature range of the hottest month
+    pub hottest_month_dry_bulb_range: f64,
+    pub dry_bulb_0_4: f64,
+    pub mean_coincident_wet_bulb_0_4: f64,
+    pub dry_bulb_1: f64,
+    pub mean_coincident_wet_bulb_1: f64,
+    pub dry_bulb_2: f64,
+    pub mean_coincident_wet_bulb_2: f64,
+    pub evaporation_wet_bulb_0_4: f64,
+    pub evaporation_mean_coincident_dry_bulb_0_4: f64,
+    pub evaporation_wet_bulb_1: f64,
+    pub evaporation_mean_coincident_dry_bulb_1: f64,
+    pub evaporation_wet_bulb_2: f64,
+    pub evaporation_mean_coincident_dry_bulb_2: f64,
+    /// Mean wind speed coincident with the 0.4% dry bulb temperature
+    pub mean_coincident_wind_speed_0_4: f64,
+    /// Prevailing wind direction coincident with the 0.4% dry bulb temperature
+    pub prevailing_coincident_wind_direction_0_4: f64,
+    pub dehumidification_dew_point_0_4: f64,
+    pub dehumidification_humidity_ratio_0_4: f64,
+    pub dehumidification_mean_coincident_dry_bulb_0_4: f64,
+    pub dehumidification_dew_point_1: f64,
+    pub dehumidification_humidity_ratio_1: f64,
+    pub dehumidification_mean_coincident_dry_bulb_1: f64,
+    pub dehumidification_dew_point_2: f64,
+    pub dehumidification_humidity_ratio_2: f64,
+    pub dehumidification_mean_coincident_dry_bulb_2: f64,
+    pub enthalpy_0_4: f64,
+    pub enthalpy_mean_coincident_dry_bulb_0_4: f64,
+    pub enthalpy_1: f64,
+    pub enthalpy_mean_coincident_dry_bulb_1: f64,
+    pub enthalpy_2: f64,
+    pub enthalpy_mean_coincident_dry_bulb_2: f64,
+    /// Number of hours between 8 a.m. and 4 p.m. with dry bulb between 12.8 and 20.6°C, only
+    /// listed from the 2009 edition onwards
+    pub hours_8_to_4_dry_bulb_12_8_to_20_6: Option<f64>,
+}
+
+/// Extreme design conditions from the ASHRAE Handbook of Fundamentals
+///
+/// Temperatures are in °C and wind speeds in m/s.
+#[derive(Debug, PartialEq)]
+pub struct ExtremeDesignConditions {
+    pub wind_speed_1: f64,
+    pub wind_speed_2_5: f64,
+    pub wind_speed_5: f64,
+    pub maximum_wet_bulb: f64,
+    /// Mean of the annual extreme minimum dry bulb temperature
+    pub mean_minimum_dry_bulb: f64,
+    /// Mean of the annual extreme maximum dry bulb temperature
+    pub mean_maximum_dry_bulb: f64,
+    pub std_dev_minimum_dry_bulb: f64,
+    pub std_dev_maximum_dry_bulb: f64,
+    /// Minimum and maximum dry bulb temperatures for the 5, 10, 20 and 50 year return periods,
+    /// only listed from the 2009 edition onwards
+    pub return_period_dry_bulb: Vec<(f64, f64)>,
+}
+
+/// Design conditions as listed in the ASHRAE Handbook of Fundamentals
+#[derive(Debug, PartialEq)]
+pub struct DesignConditions {
+    /// Source of the design conditions, e.g. "Climate Design Data 2009 ASHRAE Handbook"
+    pub source: String,
+    pub heating: Option<HeatingDesignConditions>,
+    pub cooling: Option<CoolingDesignConditions>,
+    pub extremes: Option<ExtremeDesignConditions>,
+}
+
 /// EPW File header
 #[derive(Debug)]
 pub struct Header {
     pub location: Location,
-    pub design_conditions: Option<Vec<String>>,
+    pub design_conditions: Option<DesignConditions>,
     pub typical_extreme_periods: Vec<TypicalExtremePeriod>,
     pub ground_temperatures: Vec<GroundTemperatureSample>,
     pub holidays_daylight_savings: HolidayDaylightSavings,
     pub comments: Vec<String>,
     pub data_periods: DataPeriods,
+    /// Header records with an unrecognised keyword, kept verbatim
+    pub unknown_records: Vec<String>,
 }
 
-pub fn parse_header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, EPWParseError> {
+/// Parse the header records from the lines of an EPW file
+///
+/// Header records are recognised by their keyword and read until the first data row, which is
+/// left in the iterator. Only the `LOCATION` record is required, missing records get the defaults
+/// of an hourly file without design conditions, periods, ground temperatures or holidays. Records
+/// with an unknown keyword are kept verbatim in [Header::unknown_records].
+///
+/// ## Parameters
+/// - `lines`: Iterator over the lines of the file, positioned at the first line
+///
+/// ## Returns
+/// The parsed Header or an EPWParseError
+///
+/// ## Example
+/// ```
+/// use epw_rs::header::parse_header;
+/// use epw_rs::EPWLines;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+/// let mut lines = EPWLines::new(BufReader::new(file)).peekable();
+/// let header = parse_header(&mut lines).unwrap();
+/// assert_eq!("TAMPA", header.location.city);
+/// assert!(lines.next().unwrap().unwrap().starts_with("1987,1,1,1"));
+/// ```
+pub fn parse_header<I: Iterator<Item = Result<String, EPWParseError>>>(
+    lines: &mut Peekable<I>,
+) -> Result<Header, EPWParseError> {
     let mut location: Option<Location> = None;
-    let mut design_conditions: Option<Vec<String>> = None;
-    let mut typical_extreme_periods: Option<Vec<TypicalExtremePeriod>> = None;
-    let mut ground_temperature: Option<Vec<GroundTemperatureSample>> = None;
+    let mut design_conditions: Option<DesignConditions> = None;
+    let mut typical_extreme_periods: Vec<TypicalExtremePeriod> = Vec::new();
+    let mut ground_temperature: Vec<GroundTemperatureSample> = Vec::new();
     let mut data_periods: Option<DataPeriods> = None;
     let mut holidays: Option<HolidayDaylightSavings> = None;
     let mut comments: Vec<String> = Vec::with_capacity(2);
-
-    for line in lines.by_ref().take(8) {
-        let line = line.expect("Unable to read line");
-        if line.starts_with(LOCATION_KEY) {
+    let mut unknown_records: Vec<String> = Vec::new();
+
+    while let Some(line) = lines.next_if(|line| match line {
+        Ok(val) => !_is_data_row(val),
+        Err(_) => true,
+    }) {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        } else if line.starts_with(LOCATION_KEY) {
             location = match _parse_location(&line) {
                 Ok(val) => Some(val),
                 Err(e) => {
@@ -139,12 +310,7 @@ pub fn parse_header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, EPWParse
                 }
             };
         } else if line.starts_with(GROUND_TEMPERATURES_KEY) {
-            ground_temperature = match _parse_ground_temperature(&line) {
-                Ok(val) => Some(val),
-                Err(e) => {
-                    return Err(e);
-                }
-            }
+            ground_temperature = _parse_ground_temperature(&line)?;
         } else if line.starts_with(DATA_PERIODS_KEY) {
             data_periods = match _parse_data_periods(&line) {
                 Ok(val) => Some(val),
@@ -153,10 +319,7 @@ pub fn parse_header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, EPWParse
                 }
             };
         } else if line.starts_with(TYPICAL_EXTREME_PERIODS_KEY) {
-            typical_extreme_periods = match _parse_typical_extreme_periods(&line) {
-                Ok(val) => Some(val),
-                Err(e) => return Err(e),
-            };
+            typical_extreme_periods = _parse_typical_extreme_periods(&line)?;
         } else if line.starts_with(HOLIDAYS_DAYLIGHT_SAVINGS_KEY) {
             holidays = match _parse_holiday_daylight_savings(&line) {
                 Ok(val) => Some(val),
@@ -167,12 +330,9 @@ pub fn parse_header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, EPWParse
         } else if line.starts_with(COMMENTS_KEY) {
             comments.push(_parse_comment(&line));
         } else if line.starts_with(DESIGN_CONDITIONS_KEY) {
-            design_conditions = Some(_parse_design_conditions(&line));
+            design_conditions = _parse_design_conditions(&line)?;
         } else {
-            return Err(EPWParseError::UnexpectedData(format!(
-                "Unexpected Row: {}",
-                line
-            )));
+            unknown_records.push(line);
         }
     }
 
@@ -181,49 +341,31 @@ pub fn parse_header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, EPWParse
             Some(val) => val,
             None => return Err(EPWParseError::Location("No Location Found".to_string())),
         },
-        ground_temperatures: match ground_temperature {
-            Some(val) => val,
-            None => {
-                return Err(EPWParseError::GroundTemperature(
-                    "No Ground Temperatures Found".to_string(),
-                ))
-            }
-        },
-        holidays_daylight_savings: match holidays {
-            Some(val) => val,
-            None => {
-                return Err(EPWParseError::HolidayDaylightSavings(
-                    "No Holidays/Daylight Savings Found".to_string(),
-                ))
-            }
-        },
-        data_periods: match data_periods {
-            Some(val) => val,
-            None => {
-                return Err(EPWParseError::DataPeriods(
-                    "No Data Periods Found".to_string(),
-                ))
-            }
-        },
-        typical_extreme_periods: match typical_extreme_periods {
-            Some(val) => val,
-            None => {
-                return Err(EPWParseError::TypicalExtremePeriods(
-                    "No Typical/Extreme Periods Found".to_string(),
-                ))
-            }
-        },
+        ground_temperatures: ground_temperature,
+        holidays_daylight_savings: holidays.unwrap_or_default(),
+        data_periods: data_periods.unwrap_or_default(),
+        typical_extreme_periods,
         design_conditions,
         comments,
+        unknown_records,
     })
 }
 
+/// Data rows start with the year, header records with a keyword
+fn _is_data_row(line: &str) -> bool {
+    match line.split_once(',') {
+        Some((year, _)) => year.trim().parse::<i32>().is_ok(),
+        None => false,
+    }
+}
+
 fn _parse_location(line: &str) -> Result<Location, EPWParseError> {
     if !line.starts_with(LOCATION_KEY) {
         // This should never happen
         panic!("_parse_location called with a line that doesn't start with LOCATION");
     }
-    let parts: Vec<&str> = line.split(",").collect();
+    let fields = split_fields(line);
+    let parts: Vec<&str> = fields.iter().map(String::as_str).collect();
     if parts.len() != 10 {
         return Err(EPWParseError::Location(format!(
             "Invalid Location Line: {}",
@@ -251,7 +393,18 @@ fn _parse_location(line: &str) -> Result<Location, EPWParseError> {
         }
     };
 
-    let time_zone = match FixedOffset::east_opt(parts[8].parse::<f64>().unwrap() as i32 * 3600) {
+    let time_zone_hours: f64 = match parts[8].parse() {
+        Ok(val) => val,
+        Err(e) => {
+            return Err(EPWParseError::Location(format!(
+                "Invalid Time Zone: {} [{}]",
+                parts[8], e
+            )))
+        }
+    };
+
+    // Some time zones are offset by a fraction of an hour, e.g. +5.5 for India
+    let time_zone = match FixedOffset::east_opt((time_zone_hours * 3600.).round() as i32) {
         Some(val) => val,
         None => {
             return Err(EPWParseError::Location(format!(
@@ -289,8 +442,17 @@ fn _parse_ground_temperature(line: &str) -> Result<Vec<GroundTemperatureSample>,
         panic!("_parse_ground_temperature called with a line that doesn't start with GROUND TEMPERATURES");
     }
 
-    let mut parts = line.split(",").collect::<Vec<&str>>();
-    let sample_count: u16 = parts[1].parse().unwrap();
+    let fields = split_fields(line);
+    let mut parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
+    let sample_count: u16 = match parts.get(1).unwrap_or(&"").parse() {
+        Ok(val) => val,
+        Err(e) => {
+            return Err(EPWParseError::GroundTemperature(format!(
+                "Invalid sample count: {} [{}]",
+                line, e
+            )))
+        }
+    };
     let mut samples: Vec<GroundTemperatureSample> = Vec::with_capacity(sample_count as usize);
     let mut sample_data = parts.split_off(2);
     for idx in 0..sample_count {
@@ -463,7 +625,10 @@ fn _parse_comment(line: &str) -> String {
             COMMENTS_KEY
         );
     }
-    line.splitn(2, ",").collect::<Vec<&str>>()[1].to_string()
+    match line.split_once(',') {
+        Some((_, comment)) => comment.to_string(),
+        None => String::new(),
+    }
 }
 fn _parse_data_periods(line: &str) -> Result<DataPeriods, EPWParseError> {
     if !line.starts_with(DATA_PERIODS_KEY) {
@@ -473,7 +638,8 @@ fn _parse_data_periods(line: &str) -> Result<DataPeriods, EPWParseError> {
         );
     }
 
-    let mut parts = line.split(",").collect::<Vec<&str>>();
+    let fields = split_fields(line);
+    let mut parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
 
     let period_count = match parts[1].parse() {
         Ok(val) => val,
@@ -544,7 +710,8 @@ fn _parse_typical_extreme_periods(line: &str) -> Result<Vec<TypicalExtremePeriod
         );
     }
 
-    let mut parts = line.split(",").collect::<Vec<&str>>();
+    let fields = split_fields(line);
+    let mut parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
 
     let period_count = match parts[1].parse() {
         Ok(val) => val,
@@ -601,7 +768,8 @@ fn _parse_holiday_daylight_savings(line: &str) -> Result<HolidayDaylightSavings,
         );
     }
 
-    let mut parts = line.split(",").collect::<Vec<&str>>();
+    let fields = split_fields(line);
+    let mut parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
 
     let leap_year = match parts[1] {
         "Yes" => true,
@@ -645,7 +813,8 @@ fn _parse_holiday_daylight_savings(line: &str) -> Result<HolidayDaylightSavings,
     };
 
     let mut holidays: Vec<Holiday> = Vec::with_capacity(holiday_count);
-    let mut holiday_data = parts.split_off(4);
+    // The holidays follow the keyword, leap year, daylight savings start and end and holiday count
+    let mut holiday_data = parts.split_off(5);
     for idx in 0..holiday_count {
         if holiday_data.len() < 2 {
             return Err(EPWParseError::HolidayDaylightSavings(format!(
@@ -670,7 +839,7 @@ fn _parse_holiday_daylight_savings(line: &str) -> Result<HolidayDaylightSavings,
     })
 }
 
-fn _parse_design_conditions(line: &str) -> Vec<String> {
+fn _parse_design_conditions(line: &str) -> Result<Option<DesignConditions>, EPWParseError> {
     if !line.starts_with(DESIGN_CONDITIONS_KEY) {
         panic!(
             "_parse_design_conditions called with a line that doesn't start with '{}'",
@@ -678,23 +847,241 @@ fn _parse_design_conditions(line: &str) -> Vec<String> {
         );
     }
 
-    let mut parts: VecDeque<&str> = line.split(",").collect();
-    parts.pop_front();
-    parts.into_iter().map(String::from).collect()
+    let fields = split_fields(line);
+    let parts = fields.iter().map(String::as_str).collect::<Vec<&str>>();
+    let condition_count: usize = match parts.get(1).unwrap_or(&"0").parse() {
+        Ok(val) => val,
+        Err(e) => {
+            return Err(EPWParseError::DesignConditions(format!(
+                "Invalid design condition count: {} [{}]",
+                parts[1], e
+            )))
+        }
+    };
+    if condition_count == 0 || parts.len() < 3 {
+        return Ok(None);
+    }
+
+    let heating = _design_condition_values(&parts, HEATING_KEY)?
+        .map(|values| _parse_heating_design_conditions(&values));
+    let cooling = _design_condition_values(&parts, COOLING_KEY)?
+        .map(|values| _parse_cooling_design_conditions(&values));
+    let extremes = _design_condition_values(&parts, EXTREMES_KEY)?
+        .map(|values| _parse_extreme_design_conditions(&values));
+
+    Ok(Some(DesignConditions {
+        source: parts[2].to_string(),
+        heating,
+        cooling,
+        extremes,
+    }))
+}
+
+/// Collect the values following a section keyword up to the next keyword, blanks become NaN
+///
+/// Sections may be incomplete, the parsers pad them with NaN rather than failing the header.
+fn _design_condition_values(parts: &[&str], key: &str) -> Result<Option<Vec<f64>>, EPWParseError> {
+    let start = match parts.iter().position(|part| *part == key) {
+        Some(val) => val + 1,
+        None => return Ok(None),
+    };
+
+    let mut values: Vec<f64> = Vec::new();
+    for part in &parts[start..] {
+        if [HEATING_KEY, COOLING_KEY, EXTREMES_KEY].contains(part) {
+            break;
+        }
+        let value = match part.is_empty() {
+            true => f64::NAN,
+            false => match part.parse() {
+                Ok(val) => val,
+                Err(e) => {
+                    return Err(EPWParseError::DesignConditions(format!(
+                        "Invalid {} value: {} [{}]",
+                        key, part, e
+                    )))
+                }
+            },
+        };
+        values.push(value);
+    }
+    Ok(Some(values))
+}
+
+/// Pad the values of a section with NaN up to `length`
+fn _pad_design_condition_values(values: &[f64], length: usize) -> Vec<f64> {
+    let mut values = values.to_vec();
+    if values.len() < length {
+        values.resize(length, f64::NAN);
+    }
+    values
+}
+
+fn _parse_heating_design_conditions(values: &[f64]) -> HeatingDesignConditions {
+    let values = _pad_design_condition_values(values, 15);
+    HeatingDesignConditions {
+        coldest_month: values[0] as u32,
+        dry_bulb_99_6: values[1],
+        dry_bulb_99: values[2],
+        humidification_dew_point_99_6: values[3],
+        humidification_humidity_ratio_99_6: values[4],
+        humidification_mean_coincident_dry_bulb_99_6: values[5],
+        humidification_dew_point_99: values[6],
+        humidification_humidity_ratio_99: values[7],
+        humidification_mean_coincident_dry_bulb_99: values[8],
+        coldest_month_wind_speed_0_4: values[9],
+        coldest_month_mean_coincident_dry_bulb_0_4: values[10],
+        coldest_month_wind_speed_1: values[11],
+        coldest_month_mean_coincident_dry_bulb_1: values[12],
+        mean_coincident_wind_speed_99_6: values[13],
+        prevailing_coincident_wind_direction_99_6: values[14],
+    }
+}
+
+fn _parse_cooling_design_conditions(values: &[f64]) -> CoolingDesignConditions {
+    let values = _pad_design_condition_values(values, 31);
+    CoolingDesignConditions {
+        hottest_month: values[0] as u32,
+        hottest_month_dry_bulb_range: values[1],
+        dry_bulb_0_4: values[2],
+        mean_coincident_wet_bulb_0_4: values[3],
+        dry_bulb_1: values[4],
+        mean_coincident_wet_bulb_1: values[5],
+        dry_bulb_2: values[6],
+        mean_coincident_wet_bulb_2: values[7],
+        evaporation_wet_bulb_0_4: values[8],
+        evaporation_mean_coincident_dry_bulb_0_4: values[9],
+        evaporation_wet_bulb_1: values[10],
+        evaporation_mean_coincident_dry_bulb_1: values[11],
+        evaporation_wet_bulb_2: values[12],
+        evaporation_mean_coincident_dry_bulb_2: values[13],
+        mean_coincident_wind_speed_0_4: values[14],
+        prevailing_coincident_wind_direction_0_4: values[15],
+        dehumidification_dew_point_0_4: values[16],
+        dehumidification_humidity_ratio_0_4: values[17],
+        dehumidification_mean_coincident_dry_bulb_0_4: values[18],
+        dehumidification_dew_point_1: values[19],
+        dehumidification_humidity_ratio_1: values[20],
+        dehumidification_mean_coincident_dry_bulb_1: values[21],
+        dehumidification_dew_point_2: values[22],
+        dehumidification_humidity_ratio_2: values[23],
+        dehumidification_mean_coincident_dry_bulb_2: values[24],
+        enthalpy_0_4: values[25],
+        enthalpy_mean_coincident_dry_bulb_0_4: values[26],
+        enthalpy_1: values[27],
+        enthalpy_mean_coincident_dry_bulb_1: values[28],
+        enthalpy_2: values[29],
+        enthalpy_mean_coincident_dry_bulb_2: values[30],
+        hours_8_to_4_dry_bulb_12_8_to_20_6: values.get(31).copied().filter(|v| !v.is_nan()),
+    }
+}
+
+fn _parse_extreme_design_conditions(values: &[f64]) -> ExtremeDesignConditions {
+    // Trailing blank fields don't count towards the number of values of the edition
+    let length = values
+        .iter()
+        .rposition(|v| !v.is_nan())
+        .map_or(0, |idx| idx + 1);
+    let values = _pad_design_condition_values(values, 8);
+
+    // The 2005 edition lists the extreme annual maximum before the minimum, later editions list
+    // the minimum first and add the return period values.
+    let (minimum, maximum) = match length <= 8 {
+        true => (5, 4),
+        false => (4, 5),
+    };
+
+    ExtremeDesignConditions {
+        wind_speed_1: values[0],
+        wind_speed_2_5: values[1],
+        wind_speed_5: values[2],
+        maximum_wet_bulb: values[3],
+        mean_minimum_dry_bulb: values[minimum],
+        mean_maximum_dry_bulb: values[maximum],
+        std_dev_minimum_dry_bulb: values[minimum + 2],
+        std_dev_maximum_dry_bulb: values[maximum + 2],
+        return_period_dry_bulb: values[8..length.max(8)]
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lines::EPWLines;
     use std::fs::File;
     use std::io::BufReader;
 
     const TEST_FILE: &str = "./data/USA_FL_Tampa_TMY2.epw";
 
-    fn _read_test_file() -> Lines<BufReader<File>> {
+    fn _read_test_file() -> Peekable<EPWLines<BufReader<File>>> {
         let file = File::open(TEST_FILE).unwrap();
         let reader = BufReader::new(file);
-        reader.lines()
+        EPWLines::new(reader).peekable()
+    }
+
+    fn _parse_modified_header(modify: fn(&mut Vec<String>)) -> (Header, String) {
+        let content = std::fs::read_to_string(TEST_FILE).unwrap();
+        let mut lines: Vec<String> = content.lines().take(10).map(String::from).collect();
+        modify(&mut lines);
+        let content = lines.join("\n");
+        let mut lines = EPWLines::new(content.as_bytes()).peekable();
+        let header = parse_header(&mut lines).unwrap();
+        (header, lines.next().unwrap().unwrap())
+    }
+
+    #[test]
+    fn test_parse_header_with_missing_records() {
+        let (header, next_line) = _parse_modified_header(|lines| {
+            lines.retain(|line| {
+                !line.starts_with("DESIGN CONDITIONS")
+                    && !line.starts_with("COMMENTS 2")
+                    && !line.starts_with("HOLIDAYS")
+                    && !line.starts_with("DATA PERIODS")
+            })
+        });
+
+        assert!(next_line.starts_with("1987,1,1,1,60"));
+        assert_eq!("TAMPA", header.location.city);
+        assert!(header.design_conditions.is_none());
+        assert_eq!(1, header.comments.len());
+        assert!(!header.holidays_daylight_savings.leap_year);
+        assert_eq!(1, header.data_periods.records_per_hour);
+        assert_eq!(6, header.typical_extreme_periods.len());
+    }
+
+    #[test]
+    fn test_parse_header_with_extra_records() {
+        let (header, next_line) = _parse_modified_header(|lines| {
+            lines.insert(7, "COMMENTS 3,Morphed for 2050".to_string());
+            lines.insert(8, "CUSTOM RECORD,1,Some Value".to_string());
+        });
+
+        assert!(next_line.starts_with("1987,1,1,1,60"));
+        assert_eq!(3, header.comments.len());
+        assert_eq!("Morphed for 2050", header.comments[2]);
+        assert_eq!(
+            vec!["CUSTOM RECORD,1,Some Value".to_string()],
+            header.unknown_records
+        );
+    }
+
+    #[test]
+    fn test_parse_holidays() {
+        let (header, _) = _parse_modified_header(|lines| {
+            lines[4] =
+                "HOLIDAYS/DAYLIGHT SAVINGS,Yes,0,0,2,New Year,1/1,Independence Day,7/4".to_string();
+        });
+
+        let holidays = &header.holidays_daylight_savings;
+        assert!(holidays.leap_year);
+        assert_eq!(2, holidays.holidays.len());
+        assert_eq!("New Year", holidays.holidays[0].name);
+        assert_eq!("1/1", holidays.holidays[0].date);
+        assert_eq!("Independence Day", holidays.holidays[1].name);
+        assert_eq!("7/4", holidays.holidays[1].date);
     }
 
     #[test]
@@ -778,6 +1165,65 @@ mod tests {
         assert_eq!("4/25", periods[5].end);
     }
 
+    #[test]
+    fn test_parse_design_conditions_from_file() {
+        let mut lines = _read_test_file();
+        let header = parse_header(&mut lines);
+
+        assert!(header.is_ok());
+        let header = header.unwrap();
+        let conditions = header.design_conditions.unwrap();
+        assert_eq!(
+            "Climate Design Data 2005 ASHRAE Handbook",
+            conditions.source
+        );
+
+        let heating = conditions.heating.unwrap();
+        assert_eq!(1, heating.coldest_month);
+        assert_eq!(2.6, heating.dry_bulb_99_6);
+        assert_eq!(4.8, heating.dry_bulb_99);
+        assert_eq!(3.1, heating.mean_coincident_wind_speed_99_6);
+        assert_eq!(10., heating.prevailing_coincident_wind_direction_99_6);
+
+        let cooling = conditions.cooling.unwrap();
+        assert_eq!(7, cooling.hottest_month);
+        assert_eq!(8.1, cooling.hottest_month_dry_bulb_range);
+        assert_eq!(33.6, cooling.dry_bulb_0_4);
+        assert_eq!(25.3, cooling.mean_coincident_wet_bulb_0_4);
+        assert_eq!(32.4, cooling.dry_bulb_2);
+        assert_eq!(30.7, cooling.enthalpy_mean_coincident_dry_bulb_2);
+        assert_eq!(None, cooling.hours_8_to_4_dry_bulb_12_8_to_20_6);
+
+        let extremes = conditions.extremes.unwrap();
+        assert_eq!(8.2, extremes.wind_speed_1);
+        assert_eq!(30.4, extremes.maximum_wet_bulb);
+        assert_eq!(-1.2, extremes.mean_minimum_dry_bulb);
+        assert_eq!(35., extremes.mean_maximum_dry_bulb);
+        assert!(extremes.return_period_dry_bulb.is_empty());
+    }
+
+    #[test]
+    fn test_parse_truncated_design_conditions() {
+        let (header, _) = _parse_modified_header(|lines| {
+            let cut = lines[1].find(",4,270").unwrap();
+            lines[1] = format!("{},,,,Extremes,8.2,7.4,,", &lines[1][..cut]);
+        });
+
+        let conditions = header.design_conditions.unwrap();
+        let heating = conditions.heating.unwrap();
+        assert_eq!(10., heating.prevailing_coincident_wind_direction_99_6);
+        let cooling = conditions.cooling.unwrap();
+        assert_eq!(30.6, cooling.evaporation_mean_coincident_dry_bulb_2);
+        assert!(cooling.mean_coincident_wind_speed_0_4.is_nan());
+        assert!(cooling.enthalpy_mean_coincident_dry_bulb_2.is_nan());
+        assert_eq!(None, cooling.hours_8_to_4_dry_bulb_12_8_to_20_6);
+        let extremes = conditions.extremes.unwrap();
+        assert_eq!(7.4, extremes.wind_speed_2_5);
+        assert!(extremes.wind_speed_5.is_nan());
+        assert!(extremes.mean_maximum_dry_bulb.is_nan());
+        assert!(extremes.return_period_dry_bulb.is_empty());
+    }
+
     #[test]
     fn test_parse_ground_temperature_from_file() {
         let mut lines = _read_test_file();
@@ -0,0 +1,253 @@
+/*!
+Illuminance and zenith luminance estimated from irradiance with the Perez et al. (1990) luminous
+efficacy model, "Modeling daylight availability and irradiance components from direct and global
+irradiance".
+
+EPW files derived from irradiance-only sources often have zero or missing illuminance fields. The
+model gives the luminous efficacy of the global, direct and diffuse irradiance, and the zenith
+luminance per unit of diffuse irradiance, as functions of the sky clearness and brightness, the
+zenith angle and the atmospheric precipitable water, which is estimated from the dew point
+temperature.
+
+Illuminances are in lux and the zenith luminance in cd/m². Records where the sun is below the
+horizon have no daylight, and records with missing radiation values give NaN.
+
+*/
+use crate::solar::SolarPosition;
+use crate::transposition::{perez_clearness_bin, perez_sky_brightness, perez_sky_clearness};
+use crate::{EPWFile, WeatherData};
+
+/// Global luminous efficacy coefficients a, b, c, d for each sky clearness bin
+#[rustfmt::skip]
+const GLOBAL_EFFICACY: [[f64; 4]; 8] = [
+    [96.63, -0.47, 11.50, -9.16],
+    [107.54, 0.79, 1.79, -1.19],
+    [98.73, 0.70, 4.40, -6.95],
+    [92.72, 0.56, 8.36, -8.31],
+    [86.73, 0.98, 7.10, -10.94],
+    [88.34, 1.39, 6.06, -7.60],
+    [78.63, 1.47, 4.93, -11.37],
+    [99.65, 1.86, -4.46, -3.15],
+];
+
+/// Direct luminous efficacy coefficients a, b, c, d for each sky clearness bin
+#[rustfmt::skip]
+const DIRECT_EFFICACY: [[f64; 4]; 8] = [
+    [57.20, -4.55, -2.98, 117.12],
+    [98.99, -3.46, -1.21, 12.38],
+    [109.83, -4.90, -1.71, -8.81],
+    [110.34, -5.84, -1.99, -4.56],
+    [106.36, -3.97, -1.75, -6.16],
+    [107.19, -1.25, -1.51, -26.73],
+    [105.75, 0.77, -1.26, -34.44],
+    [101.18, 1.58, -1.10, -8.29],
+];
+
+/// Diffuse luminous efficacy coefficients a, b, c, d for each sky clearness bin
+#[rustfmt::skip]
+const DIFFUSE_EFFICACY: [[f64; 4]; 8] = [
+    [97.24, -0.46, 12.00, -8.91],
+    [107.22, 1.15, 0.59, -3.95],
+    [104.97, 2.96, -5.53, -8.77],
+    [102.39, 5.59, -13.95, -13.90],
+    [100.71, 5.94, -22.75, -23.74],
+    [106.42, 3.83, -36.15, -28.83],
+    [141.88, 1.90, -53.24, -14.03],
+    [152.23, 0.35, -45.27, -7.98],
+];
+
+/// Zenith luminance coefficients a, c, c', d for each sky clearness bin
+#[rustfmt::skip]
+const ZENITH_LUMINANCE: [[f64; 4]; 8] = [
+    [40.86, 26.77, -29.59, -45.75],
+    [26.58, 14.73, 58.46, -21.25],
+    [19.34, 2.28, 100.00, 0.25],
+    [13.25, -1.39, 124.79, 15.66],
+    [14.47, -5.09, 160.09, 9.13],
+    [19.76, -3.88, 154.61, -19.21],
+    [28.39, -9.67, 151.58, -69.39],
+    [42.91, -19.62, 130.80, -164.08],
+];
+
+/// Illuminance and zenith luminance estimated for each record
+#[derive(Debug)]
+pub struct Illuminance {
+    pub global_horizontal: Vec<f64>,
+    pub direct_normal: Vec<f64>,
+    pub diffuse_horizontal: Vec<f64>,
+    pub zenith_luminance: Vec<f64>,
+}
+
+impl WeatherData {
+    /// Estimate the illuminance and zenith luminance from the radiation fields
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    ///
+    /// ## Returns
+    /// The estimated global horizontal, direct normal and diffuse horizontal illuminance and
+    /// zenith luminance for each record
+    pub fn estimate_illuminance(&self, solar_position: &[SolarPosition]) -> Illuminance {
+        let mut illuminance = Illuminance {
+            global_horizontal: Vec::with_capacity(solar_position.len()),
+            direct_normal: Vec::with_capacity(solar_position.len()),
+            diffuse_horizontal: Vec::with_capacity(solar_position.len()),
+            zenith_luminance: Vec::with_capacity(solar_position.len()),
+        };
+
+        for (idx, sun) in solar_position.iter().enumerate() {
+            let ghi = self.global_horizontal_radiation[idx];
+            let dni = self.direct_normal_radiation[idx];
+            let dhi = self.diffuse_horizontal_radiation[idx];
+            let dni_extra = self.extraterrestrial_direct_normal_radiation[idx];
+
+            let values = if ghi.is_nan() || dni.is_nan() || dhi.is_nan() || dni_extra.is_nan() {
+                [f64::NAN; 4]
+            } else if !sun.is_up() || dhi <= 0. || dni_extra <= 0. {
+                [0.; 4]
+            } else {
+                let z = sun.zenith.to_radians();
+                let brightness = perez_sky_brightness(dhi, dni_extra, sun.zenith);
+                let bin = perez_clearness_bin(perez_sky_clearness(dni, dhi, sun.zenith));
+                // Atmospheric precipitable water in cm
+                let water = (0.07 * self.dew_point_temperature[idx] - 0.075).exp();
+
+                let g = GLOBAL_EFFICACY[bin];
+                let d = DIRECT_EFFICACY[bin];
+                let f = DIFFUSE_EFFICACY[bin];
+                let l = ZENITH_LUMINANCE[bin];
+                [
+                    ghi * (g[0] + g[1] * water + g[2] * z.cos() + g[3] * brightness.ln()),
+                    dni * (d[0] + d[1] * water + d[2] * (5.73 * z - 5.).exp() + d[3] * brightness),
+                    dhi * (f[0] + f[1] * water + f[2] * z.cos() + f[3] * brightness.ln()),
+                    dhi * (l[0] + l[1] * z.cos() + l[2] * (-3. * z).exp() + l[3] * brightness),
+                ]
+            };
+
+            // f64::max would turn the missing values into zeros
+            let values = values.map(|v| if v < 0. { 0. } else { v });
+            illuminance.global_horizontal.push(values[0]);
+            illuminance.direct_normal.push(values[1]);
+            illuminance.diffuse_horizontal.push(values[2]);
+            illuminance.zenith_luminance.push(values[3]);
+        }
+        illuminance
+    }
+
+    /// Replace the illuminance and zenith luminance fields with values estimated from the
+    /// radiation fields
+    ///
+    /// Files where the missing values are written as zeros should pass `only_missing: false`.
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_illuminance(&mut self, solar_position: &[SolarPosition], only_missing: bool) {
+        let estimated = self.estimate_illuminance(solar_position);
+        for (dest, values) in [
+            (
+                &mut self.global_horizontal_illuminance,
+                estimated.global_horizontal,
+            ),
+            (&mut self.direct_normal_illuminance, estimated.direct_normal),
+            (
+                &mut self.diffuse_horizontal_illuminance,
+                estimated.diffuse_horizontal,
+            ),
+            (&mut self.zenith_luminance, estimated.zenith_luminance),
+        ] {
+            for (current, value) in dest.iter_mut().zip(values) {
+                if !only_missing || current.is_nan() {
+                    *current = value;
+                }
+            }
+        }
+    }
+}
+
+impl EPWFile {
+    /// Estimate the illuminance and zenith luminance from the radiation fields
+    ///
+    /// ## Returns
+    /// The estimated global horizontal, direct normal and diffuse horizontal illuminance and
+    /// zenith luminance for each record
+    pub fn estimate_illuminance(&self) -> Illuminance {
+        self.data.estimate_illuminance(&self.solar_position())
+    }
+
+    /// Replace the illuminance and zenith luminance fields with values estimated from the
+    /// radiation fields
+    ///
+    /// ## Parameters
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_illuminance(&mut self, only_missing: bool) {
+        let solar_position = self.solar_position();
+        self.data.fill_illuminance(&solar_position, only_missing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_illuminance() {
+        // The illuminance of the TMY2 data set was computed with the same model
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let estimated = epw.estimate_illuminance();
+        let data = &epw.data;
+
+        assert_eq!(0., estimated.global_horizontal[0]);
+        for idx in [10, 12, 14, 4356, 4360] {
+            for (expected, actual) in [
+                (
+                    data.global_horizontal_illuminance[idx],
+                    estimated.global_horizontal[idx],
+                ),
+                (
+                    data.direct_normal_illuminance[idx],
+                    estimated.direct_normal[idx],
+                ),
+                (
+                    data.diffuse_horizontal_illuminance[idx],
+                    estimated.diffuse_horizontal[idx],
+                ),
+                (data.zenith_luminance[idx], estimated.zenith_luminance[idx]),
+            ] {
+                assert!(
+                    (expected - actual).abs() < 0.05 * expected + 100.,
+                    "{} {} {}",
+                    idx,
+                    expected,
+                    actual
+                );
+            }
+        }
+
+        epw.data.zenith_luminance[12] = f64::NAN;
+        let global = epw.data.global_horizontal_illuminance.clone();
+        epw.fill_illuminance(true);
+        assert_eq!(
+            estimated.zenith_luminance[12],
+            epw.data.zenith_luminance[12]
+        );
+        assert_eq!(global, epw.data.global_horizontal_illuminance);
+
+        // Missing radiation gives missing illuminance, which stays missing when filled
+        for (idx, radiation) in [
+            (4356, &mut epw.data.global_horizontal_radiation),
+            (4358, &mut epw.data.direct_normal_radiation),
+            (4360, &mut epw.data.diffuse_horizontal_radiation),
+        ] {
+            radiation[idx] = f64::NAN;
+            epw.data.global_horizontal_illuminance[idx] = f64::NAN;
+        }
+        let estimated = epw.estimate_illuminance();
+        epw.fill_illuminance(true);
+        for idx in [4356, 4358, 4360] {
+            assert!(estimated.global_horizontal[idx].is_nan());
+            assert!(estimated.zenith_luminance[idx].is_nan());
+            assert!(epw.data.global_horizontal_illuminance[idx].is_nan());
+        }
+    }
+}
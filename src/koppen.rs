@@ -0,0 +1,330 @@
+/*!
+Köppen-Geiger climate classification, with the criteria of Peel et al. (2007), "Updated world map
+of the Köppen-Geiger climate classification".
+
+The classification uses the monthly mean dry bulb temperatures and the monthly totals of the liquid
+precipitation depth. Summer is April to September in the northern hemisphere and October to March
+in the southern one. The main climates are tested in this order:
+- E, polar: the warmest month mean is below 10°C, ET (tundra) above 0°C and EF (ice cap) otherwise
+- B, arid: the annual precipitation is below 10 times the dryness threshold, BW (desert) below 5
+  times and BS (steppe) otherwise, with h (hot) for an annual mean of at least 18°C and k (cold)
+  otherwise
+- A, tropical: the coldest month mean is at least 18°C, Af (rainforest) when the driest month has
+  at least 60 mm, Am (monsoon) when it has at least `100 - P / 25` mm and Aw (savanna) otherwise
+- C, temperate: the coldest month mean is above 0°C
+- D, continental: otherwise
+
+C and D climates get a second letter, s (dry summer) when the driest summer month has less than
+40 mm and less than a third of the wettest winter month, w (dry winter) when the driest winter
+month has less than a tenth of the wettest summer month, and f (no dry season) otherwise. The
+third letter is a (hot summer) when the warmest month mean is at least 22°C, b (warm summer) when
+at least four months are above 10°C, d (very cold winter) for D climates with a coldest month
+below -38°C, and c (cold summer) otherwise.
+
+The dryness threshold in mm is twice the annual mean temperature, plus 28 when at least 70% of the
+precipitation falls in summer, plus 0 when at least 70% falls in winter, and plus 14 otherwise.
+
+Many files don't have precipitation values. The arid climates can't be identified without them,
+and the letters that depend on precipitation are replaced by `?`, e.g. `C?a`.
+
+*/
+use crate::header::Location;
+use crate::summary::{monthly_means, monthly_totals, Statistics};
+use crate::{EPWFile, WeatherData};
+
+/// Köppen-Geiger climate with the values and criteria it was derived from
+#[derive(Debug)]
+pub struct KoppenClimate {
+    /// Köppen-Geiger code, e.g. `Cfa`
+    pub code: String,
+    /// Annual mean dry bulb temperature in °C
+    pub annual_mean_temperature: f64,
+    /// Mean dry bulb temperature of the coldest month in °C
+    pub coldest_month_temperature: f64,
+    /// Mean dry bulb temperature of the warmest month in °C
+    pub warmest_month_temperature: f64,
+    /// Number of months with a mean dry bulb temperature above 10°C
+    pub months_above_10: usize,
+    /// Annual precipitation in mm, NaN without precipitation values
+    pub annual_precipitation: f64,
+    /// Precipitation of the driest month in mm, NaN without precipitation values
+    pub driest_month_precipitation: f64,
+    /// Precipitation of the driest summer month in mm, NaN without precipitation values
+    pub summer_driest_month_precipitation: f64,
+    /// Precipitation of the wettest summer month in mm, NaN without precipitation values
+    pub summer_wettest_month_precipitation: f64,
+    /// Precipitation of the driest winter month in mm, NaN without precipitation values
+    pub winter_driest_month_precipitation: f64,
+    /// Precipitation of the wettest winter month in mm, NaN without precipitation values
+    pub winter_wettest_month_precipitation: f64,
+    /// Dryness threshold in mm, NaN without precipitation values
+    pub dryness_threshold: f64,
+    /// The criteria that decided each letter of the code, in order
+    pub criteria: Vec<String>,
+}
+
+impl WeatherData {
+    /// Classify the climate in the Köppen-Geiger classification
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station, used for the hemisphere
+    ///
+    /// ## Returns
+    /// The climate code and the values and criteria it was derived from
+    pub fn koppen_climate(&self, location: &Location) -> KoppenClimate {
+        let temperatures = monthly_means(&self.timestamp, &self.dry_bulb_temperature);
+        let known_temperatures = temperatures.iter().filter(|t| !t.is_nan());
+        let coldest = known_temperatures
+            .clone()
+            .fold(f64::INFINITY, |a, b| a.min(*b));
+        let warmest = known_temperatures
+            .clone()
+            .fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+        let months_above_10 = known_temperatures.filter(|t| **t > 10.).count();
+
+        let has_precipitation = self.liquid_precipitation_depth.iter().any(|p| !p.is_nan());
+        let precipitation = match has_precipitation {
+            true => monthly_totals(&self.timestamp, &self.liquid_precipitation_depth),
+            false => vec![f64::NAN; 12],
+        };
+        // Months 4 to 9 are the summer in the northern hemisphere
+        let is_summer = |idx: &usize| (3..=8).contains(idx) == (location.latitude >= 0.);
+        let summer: Vec<f64> = (0..12)
+            .filter(is_summer)
+            .map(|i| precipitation[i])
+            .collect();
+        let winter: Vec<f64> = (0..12)
+            .filter(|i| !is_summer(i))
+            .map(|i| precipitation[i])
+            .collect();
+        let annual_precipitation: f64 = precipitation.iter().sum();
+
+        let mut climate = KoppenClimate {
+            code: String::new(),
+            annual_mean_temperature: Statistics::from_values(&self.dry_bulb_temperature).mean,
+            coldest_month_temperature: coldest,
+            warmest_month_temperature: warmest,
+            months_above_10,
+            annual_precipitation,
+            driest_month_precipitation: Statistics::from_values(&precipitation).minimum,
+            summer_driest_month_precipitation: Statistics::from_values(&summer).minimum,
+            summer_wettest_month_precipitation: Statistics::from_values(&summer).maximum,
+            winter_driest_month_precipitation: Statistics::from_values(&winter).minimum,
+            winter_wettest_month_precipitation: Statistics::from_values(&winter).maximum,
+            dryness_threshold: f64::NAN,
+            criteria: Vec::new(),
+        };
+        if has_precipitation {
+            let summer_fraction = summer.iter().sum::<f64>() / annual_precipitation;
+            climate.dryness_threshold = 2. * climate.annual_mean_temperature
+                + match summer_fraction {
+                    f if f >= 0.7 => 28.,
+                    f if f <= 0.3 => 0.,
+                    _ => 14.,
+                };
+        }
+        climate._classify(has_precipitation);
+        climate
+    }
+}
+
+impl EPWFile {
+    /// Classify the climate of the file in the Köppen-Geiger classification
+    ///
+    /// ## Returns
+    /// The climate code and the values and criteria it was derived from
+    pub fn koppen_climate(&self) -> KoppenClimate {
+        self.data.koppen_climate(&self.header.location)
+    }
+}
+
+impl KoppenClimate {
+    fn _classify(&mut self, has_precipitation: bool) {
+        let warmest = self.warmest_month_temperature;
+        let coldest = self.coldest_month_temperature;
+        let precipitation = self.annual_precipitation;
+        let threshold = self.dryness_threshold;
+
+        if warmest < 10. {
+            self._decide('E', format!("warmest month {:.1}°C < 10°C: polar", warmest));
+            match warmest > 0. {
+                true => self._decide('T', format!("warmest month {:.1}°C > 0°C: tundra", warmest)),
+                false => self._decide(
+                    'F',
+                    format!("warmest month {:.1}°C ≤ 0°C: ice cap", warmest),
+                ),
+            }
+            return;
+        }
+        if !has_precipitation {
+            self.criteria
+                .push("no precipitation values: arid climates not tested".to_string());
+        } else if precipitation < 10. * threshold {
+            self._decide(
+                'B',
+                format!(
+                    "annual precipitation {:.0} mm < 10 × dryness threshold {:.0} mm: arid",
+                    precipitation, threshold
+                ),
+            );
+            match precipitation < 5. * threshold {
+                true => self._decide(
+                    'W',
+                    "annual precipitation < 5 × dryness threshold: desert".to_string(),
+                ),
+                false => self._decide(
+                    'S',
+                    "annual precipitation ≥ 5 × dryness threshold: steppe".to_string(),
+                ),
+            }
+            let mean = self.annual_mean_temperature;
+            match mean >= 18. {
+                true => self._decide('h', format!("annual mean {:.1}°C ≥ 18°C: hot", mean)),
+                false => self._decide('k', format!("annual mean {:.1}°C < 18°C: cold", mean)),
+            }
+            return;
+        }
+
+        if coldest >= 18. {
+            self._decide(
+                'A',
+                format!("coldest month {:.1}°C ≥ 18°C: tropical", coldest),
+            );
+            let driest = self.driest_month_precipitation;
+            if !has_precipitation {
+                self._decide(
+                    '?',
+                    "no precipitation values: dry season unknown".to_string(),
+                );
+            } else if driest >= 60. {
+                self._decide(
+                    'f',
+                    format!("driest month {:.0} mm ≥ 60 mm: rainforest", driest),
+                );
+            } else if driest >= 100. - precipitation / 25. {
+                self._decide(
+                    'm',
+                    format!(
+                        "driest month {:.0} mm ≥ {:.0} mm: monsoon",
+                        driest,
+                        100. - precipitation / 25.
+                    ),
+                );
+            } else {
+                self._decide(
+                    'w',
+                    format!(
+                        "driest month {:.0} mm < {:.0} mm: savanna",
+                        driest,
+                        100. - precipitation / 25.
+                    ),
+                );
+            }
+            return;
+        }
+
+        let continental = coldest <= 0.;
+        match continental {
+            true => self._decide(
+                'D',
+                format!("coldest month {:.1}°C ≤ 0°C: continental", coldest),
+            ),
+            false => self._decide(
+                'C',
+                format!("coldest month {:.1}°C > 0°C: temperate", coldest),
+            ),
+        }
+
+        let summer_driest = self.summer_driest_month_precipitation;
+        let summer_wettest = self.summer_wettest_month_precipitation;
+        let winter_driest = self.winter_driest_month_precipitation;
+        let winter_wettest = self.winter_wettest_month_precipitation;
+        if !has_precipitation {
+            self._decide(
+                '?',
+                "no precipitation values: dry season unknown".to_string(),
+            );
+        } else if summer_driest < 40. && summer_driest < winter_wettest / 3. {
+            self._decide(
+                's',
+                format!(
+                    "driest summer month {:.0} mm < 40 mm and < wettest winter month {:.0} mm / 3: dry summer",
+                    summer_driest, winter_wettest
+                ),
+            );
+        } else if winter_driest < summer_wettest / 10. {
+            self._decide(
+                'w',
+                format!(
+                    "driest winter month {:.0} mm < wettest summer month {:.0} mm / 10: dry winter",
+                    winter_driest, summer_wettest
+                ),
+            );
+        } else {
+            self._decide('f', "no dry summer or winter: no dry season".to_string());
+        }
+
+        let months = self.months_above_10;
+        if warmest >= 22. {
+            self._decide(
+                'a',
+                format!("warmest month {:.1}°C ≥ 22°C: hot summer", warmest),
+            );
+        } else if months >= 4 {
+            self._decide('b', format!("{} months above 10°C: warm summer", months));
+        } else if continental && coldest < -38. {
+            self._decide(
+                'd',
+                format!("coldest month {:.1}°C < -38°C: very cold winter", coldest),
+            );
+        } else {
+            self._decide('c', format!("{} months above 10°C: cold summer", months));
+        }
+    }
+
+    fn _decide(&mut self, letter: char, criterion: String) {
+        self.code.push(letter);
+        self.criteria.push(format!("{}: {}", letter, criterion));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_koppen_climate() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let climate = epw.koppen_climate();
+        assert_eq!("C?a", climate.code);
+        assert!(climate.annual_precipitation.is_nan());
+        assert_eq!(4, climate.criteria.len());
+
+        // Wet summers and dry winters, about 1200 mm a year
+        for (timestamp, precipitation) in epw
+            .data
+            .timestamp
+            .iter()
+            .zip(epw.data.liquid_precipitation_depth.iter_mut())
+        {
+            *precipitation = match chrono::Datelike::month(timestamp) {
+                6..=9 => 0.3,
+                _ => 0.05,
+            };
+        }
+        let climate = epw.koppen_climate();
+        assert_eq!("Cfa", climate.code);
+        assert_eq!(
+            2. * climate.annual_mean_temperature + 28.,
+            climate.dryness_threshold
+        );
+
+        epw.header.location.latitude = -epw.header.location.latitude;
+        let climate = epw.koppen_climate();
+        assert!(climate.summer_wettest_month_precipitation < 40.);
+        assert_eq!("Csa", climate.code);
+
+        epw.data.liquid_precipitation_depth = vec![0.01; 8760];
+        assert_eq!("BWh", epw.koppen_climate().code);
+    }
+}
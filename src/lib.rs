@@ -1,11 +1,41 @@
 #![doc = include_str!("../README.md")]
+pub mod bundle;
+pub mod climate_zone;
+#[cfg(feature = "compression")]
+mod compression;
+pub mod consistency;
+pub mod ddy;
+pub mod decomposition;
+pub mod degree_days;
+pub mod design_conditions;
 pub mod epw_file;
 mod error;
+pub mod extraterrestrial;
+pub mod gap_filling;
 pub mod header;
+pub mod illuminance;
+pub mod koppen;
+mod lines;
+pub mod periods;
+pub mod psychrometrics;
+pub mod quality;
+pub mod resample;
+pub mod selection;
+pub mod sky;
+pub mod solar;
+pub mod stat;
+pub mod summary;
+pub mod transposition;
+mod wea;
 pub mod weather_data;
+pub mod wind;
 
+pub use bundle::EPWBundle;
+pub use ddy::DDYFile;
 pub use epw_file::EPWFile;
 pub use header::Header;
+pub use lines::EPWLines;
+pub use stat::STATFile;
 pub use weather_data::WeatherData;
 
 #[cfg(feature = "polars")]
@@ -0,0 +1,97 @@
+/*!
+Line and field splitting shared by the header and data parsers.
+
+EPW files are written by many different tools, so the reader is lenient about the encoding
+details that don't change the content: a UTF-8 byte order mark, CRLF line endings, whitespace
+around values, double quoted fields containing commas, and Latin-1 encoded text (common for city
+names written by older Windows tools).
+
+*/
+use crate::error::EPWParseError;
+use std::io::BufRead;
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// Iterator over the lines of an EPW file, decoded and stripped of line endings
+///
+/// Wrapped in a [std::iter::Peekable], it is the input of [crate::header::parse_header], which
+/// leaves the first data row in it.
+pub struct EPWLines<R: BufRead> {
+    reader: R,
+    first_line: bool,
+}
+
+impl<R: BufRead> EPWLines<R> {
+    /// Read the lines of `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            first_line: true,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for EPWLines<R> {
+    type Item = Result<String, EPWParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer: Vec<u8> = Vec::new();
+        match self.reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(EPWParseError::Io(e.to_string()))),
+        }
+
+        let mut line = match String::from_utf8(buffer) {
+            Ok(val) => val,
+            // Every byte is a valid Latin-1 character, and Latin-1 maps directly onto the first
+            // 256 unicode code points
+            Err(e) => e.into_bytes().into_iter().map(char::from).collect(),
+        };
+
+        if self.first_line {
+            self.first_line = false;
+            if line.starts_with(BYTE_ORDER_MARK) {
+                line.remove(0);
+            }
+        }
+
+        let trimmed_length = line.trim_end().len();
+        line.truncate(trimmed_length);
+        Some(Ok(line))
+    }
+}
+
+/// Split a line into comma separated fields
+///
+/// Fields are trimmed of surrounding whitespace, and commas inside double quoted fields are kept
+/// as part of the field. A doubled quote inside a quoted field is an escaped quote.
+pub(crate) fn split_fields(line: &str) -> Vec<String> {
+    if !line.contains('"') {
+        return line
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .collect();
+    }
+
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                fields.push(field.trim().to_string());
+                field.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
@@ -0,0 +1,122 @@
+/*!
+Weather data of the typical and extreme periods listed in the header.
+
+The TYPICAL/EXTREME PERIODS record gives the start and end of each period as `month/day` strings,
+e.g. `7/ 6` to `7/12`. A period whose end is before its start wraps around the end of the year,
+e.g. `12/22` to `1/ 5`, and its records are returned in calendar order, December before January,
+even though they are stored the other way around in a typical year file.
+
+*/
+use crate::error::EPWParseError;
+use crate::header::TypicalExtremePeriod;
+use crate::{EPWFile, WeatherData};
+use chrono::Datelike;
+
+impl EPWFile {
+    /// Select the records of a typical or extreme period
+    ///
+    /// ## Parameters
+    /// - `period`: The period, usually one of [crate::Header::typical_extreme_periods]
+    ///
+    /// ## Returns
+    /// A new WeatherData with the records of the period, or an EPWParseError when its start or
+    /// end is not a valid `month/day`
+    pub fn period(&self, period: &TypicalExtremePeriod) -> Result<WeatherData, EPWParseError> {
+        let start = _parse_month_day(&period.start)?;
+        let end = _parse_month_day(&period.end)?;
+        let month_day: Vec<(u32, u32)> = self
+            .data
+            .timestamp
+            .iter()
+            .map(|timestamp| (timestamp.month(), timestamp.day()))
+            .collect();
+
+        let indices: Vec<usize> = match start <= end {
+            true => (0..month_day.len())
+                .filter(|idx| month_day[*idx] >= start && month_day[*idx] <= end)
+                .collect(),
+            false => (0..month_day.len())
+                .filter(|idx| month_day[*idx] >= start)
+                .chain((0..month_day.len()).filter(|idx| month_day[*idx] <= end))
+                .collect(),
+        };
+        Ok(self.data.select_indices(&indices))
+    }
+
+    /// Iterate over the typical and extreme periods of the header with their records
+    ///
+    /// ## Returns
+    /// An iterator over each period and its records, see [EPWFile::period]
+    pub fn periods(
+        &self,
+    ) -> impl Iterator<Item = (&TypicalExtremePeriod, Result<WeatherData, EPWParseError>)> {
+        self.header
+            .typical_extreme_periods
+            .iter()
+            .map(|period| (period, self.period(period)))
+    }
+}
+
+/// Parse a `month/day` string such as `12/22` or `1/ 5`
+fn _parse_month_day(value: &str) -> Result<(u32, u32), EPWParseError> {
+    let parts: Vec<&str> = value.split('/').map(str::trim).collect();
+    let (month, day) = match parts.as_slice() {
+        [month, day] => (month.parse::<u32>(), day.parse::<u32>()),
+        _ => {
+            return Err(EPWParseError::TypicalExtremePeriods(format!(
+                "Invalid period date: {}",
+                value
+            )))
+        }
+    };
+    match (month, day) {
+        (Ok(month), Ok(day)) if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+            Ok((month, day))
+        }
+        _ => Err(EPWParseError::TypicalExtremePeriods(format!(
+            "Invalid period date: {}",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let periods: Vec<_> = epw.periods().collect();
+        assert_eq!(6, periods.len());
+
+        let (summer, data) = &periods[0];
+        assert_eq!("7/ 6", summer.start);
+        let data = data.as_ref().unwrap();
+        assert_eq!(7 * 24, data.len());
+        assert_eq!((7, 6), (data.timestamp[0].month(), data.timestamp[0].day()));
+
+        // 12/22 to 1/ 5 wraps around the end of the year
+        let (winter, data) = &periods[3];
+        assert_eq!("12/22", winter.start);
+        let data = data.as_ref().unwrap();
+        assert_eq!(15 * 24, data.len());
+        assert_eq!(
+            (12, 22),
+            (data.timestamp[0].month(), data.timestamp[0].day())
+        );
+        let last = data.timestamp[data.len() - 1];
+        assert_eq!(
+            (1, 5, 23),
+            (last.month(), last.day(), chrono::Timelike::hour(&last))
+        );
+
+        let invalid = TypicalExtremePeriod {
+            name: "Invalid".to_string(),
+            period_type: crate::header::PeriodType::Typical,
+            start: "13/1".to_string(),
+            end: "1/5".to_string(),
+        };
+        assert!(epw.period(&invalid).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+/*!
+Psychrometric properties of moist air, using the formulations of the 2017 ASHRAE Handbook of
+Fundamentals, chapter 1.
+
+The scalar functions work in the units used by [WeatherData]: temperatures in °C, pressures in
+Pascals and relative humidity in percent. Humidity ratios are in kg of water per kg of dry air,
+enthalpies in kJ per kg of dry air and specific volumes in m³ per kg of dry air. NaN inputs give
+NaN outputs.
+
+The [WeatherData] methods compute a derived column for every record. The vapor pressure is taken
+from the dew point temperature, or from the relative humidity when the dew point is missing.
+
+*/
+use crate::WeatherData;
+
+/// Ratio of the molecular mass of water vapor to dry air
+const MOLECULAR_MASS_RATIO: f64 = 0.621945;
+
+/// Standard atmospheric pressure in Pascals at an elevation in meters (ASHRAE eq. 3)
+pub fn standard_pressure(elevation: f64) -> f64 {
+    101325. * (1. - 2.25577e-5 * elevation).powf(5.2559)
+}
+
+/// Saturation vapor pressure in Pascals over ice below 0°C and over liquid water above
+/// (ASHRAE eq. 5 and 6)
+pub fn saturation_vapor_pressure(temperature: f64) -> f64 {
+    let t = temperature + 273.15;
+    let ln_pws = match temperature < 0. {
+        true => {
+            -5.6745359e3 / t + 6.3925247 - 9.6778430e-3 * t
+                + 6.2215701e-7 * t.powi(2)
+                + 2.0747825e-9 * t.powi(3)
+                - 9.4840240e-13 * t.powi(4)
+                + 4.1635019 * t.ln()
+        }
+        false => {
+            -5.8002206e3 / t + 1.3914993 - 4.8640239e-2 * t + 4.1764768e-5 * t.powi(2)
+                - 1.4452093e-8 * t.powi(3)
+                + 6.5459673 * t.ln()
+        }
+    };
+    ln_pws.exp()
+}
+
+/// Partial pressure of water vapor in Pascals at a dew point temperature
+pub fn partial_vapor_pressure(dew_point_temperature: f64) -> f64 {
+    saturation_vapor_pressure(dew_point_temperature)
+}
+
+/// Dew point temperature at a partial pressure of water vapor in Pascals
+pub fn dew_point_temperature(partial_vapor_pressure: f64) -> f64 {
+    if partial_vapor_pressure.is_nan() || partial_vapor_pressure <= 0. {
+        return f64::NAN;
+    }
+    _solve(-100., 200., |t| {
+        saturation_vapor_pressure(t) - partial_vapor_pressure
+    })
+}
+
+/// Relative humidity in percent from the dry bulb and dew point temperatures
+pub fn relative_humidity(dry_bulb_temperature: f64, dew_point_temperature: f64) -> f64 {
+    100. * partial_vapor_pressure(dew_point_temperature)
+        / saturation_vapor_pressure(dry_bulb_temperature)
+}
+
+/// Humidity ratio in kg/kg from the partial vapor pressure and total pressure (ASHRAE eq. 20)
+pub fn humidity_ratio(partial_vapor_pressure: f64, pressure: f64) -> f64 {
+    MOLECULAR_MASS_RATIO * partial_vapor_pressure / (pressure - partial_vapor_pressure)
+}
+
+/// Partial vapor pressure in Pascals from the humidity ratio and total pressure
+pub fn vapor_pressure_from_humidity_ratio(humidity_ratio: f64, pressure: f64) -> f64 {
+    pressure * humidity_ratio / (MOLECULAR_MASS_RATIO + humidity_ratio)
+}
+
+/// Specific enthalpy of moist air in kJ/kg of dry air (ASHRAE eq. 32)
+pub fn enthalpy(dry_bulb_temperature: f64, humidity_ratio: f64) -> f64 {
+    1.006 * dry_bulb_temperature + humidity_ratio * (2501. + 1.86 * dry_bulb_temperature)
+}
+
+/// Specific volume of moist air in m³/kg of dry air (ASHRAE eq. 28)
+pub fn specific_volume(dry_bulb_temperature: f64, humidity_ratio: f64, pressure: f64) -> f64 {
+    0.287042 * (dry_bulb_temperature + 273.15) * (1. + 1.607858 * humidity_ratio)
+        / (pressure / 1000.)
+}
+
+/// Thermodynamic wet bulb temperature in °C (ASHRAE eq. 33 and 35, solved iteratively)
+pub fn wet_bulb_temperature(dry_bulb_temperature: f64, humidity_ratio: f64, pressure: f64) -> f64 {
+    if dry_bulb_temperature.is_nan() || humidity_ratio.is_nan() || pressure.is_nan() {
+        return f64::NAN;
+    }
+
+    let dew_point =
+        dew_point_temperature(vapor_pressure_from_humidity_ratio(humidity_ratio, pressure));
+    let t = dry_bulb_temperature;
+    _solve(dew_point.min(t), t, |wet_bulb| {
+        let saturated = humidity_ratio_at_saturation(wet_bulb, pressure);
+        let estimated = match wet_bulb < 0. {
+            true => {
+                ((2830. - 0.24 * wet_bulb) * saturated - 1.006 * (t - wet_bulb))
+                    / (2830. + 1.86 * t - 2.1 * wet_bulb)
+            }
+            false => {
+                ((2501. - 2.326 * wet_bulb) * saturated - 1.006 * (t - wet_bulb))
+                    / (2501. + 1.86 * t - 4.186 * wet_bulb)
+            }
+        };
+        estimated - humidity_ratio
+    })
+}
+
+/// Humidity ratio of saturated air in kg/kg
+pub fn humidity_ratio_at_saturation(temperature: f64, pressure: f64) -> f64 {
+    humidity_ratio(saturation_vapor_pressure(temperature), pressure)
+}
+
+/// Find the root of an increasing function by bisection
+fn _solve<F: Fn(f64) -> f64>(low: f64, high: f64, f: F) -> f64 {
+    let (mut low, mut high) = (low, high);
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        match f(mid) > 0. {
+            true => high = mid,
+            false => low = mid,
+        }
+        if high - low < 1e-6 {
+            break;
+        }
+    }
+    0.5 * (low + high)
+}
+
+impl WeatherData {
+    /// Partial pressure of water vapor in Pascals for each record
+    pub fn partial_vapor_pressure(&self) -> Vec<f64> {
+        self.dew_point_temperature
+            .iter()
+            .zip(&self.dry_bulb_temperature)
+            .zip(&self.relative_humidity)
+            .map(|((dew_point, dry_bulb), rh)| match dew_point.is_nan() {
+                true => rh / 100. * saturation_vapor_pressure(*dry_bulb),
+                false => partial_vapor_pressure(*dew_point),
+            })
+            .collect()
+    }
+
+    /// Humidity ratio in kg/kg for each record
+    pub fn humidity_ratio(&self) -> Vec<f64> {
+        self.partial_vapor_pressure()
+            .iter()
+            .zip(&self.atmospheric_pressure)
+            .map(|(pw, pressure)| humidity_ratio(*pw, *pressure))
+            .collect()
+    }
+
+    /// Thermodynamic wet bulb temperature in °C for each record
+    pub fn wet_bulb_temperature(&self) -> Vec<f64> {
+        self.humidity_ratio()
+            .iter()
+            .zip(&self.dry_bulb_temperature)
+            .zip(&self.atmospheric_pressure)
+            .map(|((w, dry_bulb), pressure)| wet_bulb_temperature(*dry_bulb, *w, *pressure))
+            .collect()
+    }
+
+    /// Specific enthalpy in kJ/kg of dry air for each record
+    pub fn enthalpy(&self) -> Vec<f64> {
+        self.humidity_ratio()
+            .iter()
+            .zip(&self.dry_bulb_temperature)
+            .map(|(w, dry_bulb)| enthalpy(*dry_bulb, *w))
+            .collect()
+    }
+
+    /// Specific volume in m³/kg of dry air for each record
+    pub fn specific_volume(&self) -> Vec<f64> {
+        self.humidity_ratio()
+            .iter()
+            .zip(&self.dry_bulb_temperature)
+            .zip(&self.atmospheric_pressure)
+            .map(|((w, dry_bulb), pressure)| specific_volume(*dry_bulb, *w, *pressure))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() < tolerance,
+            "expected {} got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_saturation_vapor_pressure() {
+        // ASHRAE Fundamentals 2017 chapter 1, table 3
+        _assert_close(611.2, saturation_vapor_pressure(0.), 0.1);
+        _assert_close(2339.3, saturation_vapor_pressure(20.), 0.5);
+        _assert_close(259.9, saturation_vapor_pressure(-10.), 0.1);
+    }
+
+    #[test]
+    fn test_moist_air_properties() {
+        // 30°C dry bulb and 25°C wet bulb at standard pressure, humidity ratio from ASHRAE eq. 35
+        let pressure = 101325.;
+        let saturated = humidity_ratio_at_saturation(25., pressure);
+        let w =
+            ((2501. - 2.326 * 25.) * saturated - 1.006 * 5.) / (2501. + 1.86 * 30. - 4.186 * 25.);
+        _assert_close(0.01796, w, 0.00001);
+
+        _assert_close(25., wet_bulb_temperature(30., w, pressure), 0.001);
+        _assert_close(76.08, enthalpy(30., w), 0.01);
+        _assert_close(0.8836, specific_volume(30., w, pressure), 0.0001);
+
+        let dew_point = dew_point_temperature(vapor_pressure_from_humidity_ratio(w, pressure));
+        _assert_close(
+            w,
+            humidity_ratio(partial_vapor_pressure(dew_point), pressure),
+            1e-7,
+        );
+        _assert_close(
+            100. * vapor_pressure_from_humidity_ratio(w, pressure) / saturation_vapor_pressure(30.),
+            relative_humidity(30., dew_point),
+            0.001,
+        );
+    }
+}
@@ -0,0 +1,413 @@
+/*!
+Data completeness and quality checks.
+
+The [QualityReport] of a file lists:
+- The missing (NaN) values of each numeric column, in total and for each calendar month
+- Duplicate and out of order timestamps, and missing records, found by comparing each timestamp
+  with the latest earlier one. Typical year files join months of different years, so when the year
+  changes the timestamps are compared as if they were in the same year, unless that puts the
+  record before the previous one, as happens at the start of a new year in multi-year files.
+- Stuck sensors, runs of at least [STUCK_SENSOR_HOURS] hours of the same value in the fields where
+  that is implausible: temperatures, humidity, pressure and wind
+- Values outside the physical range of their field, e.g. a relative humidity above 100% or a
+  negative irradiance
+
+The [fmt::Display] implementation renders the report as text.
+
+*/
+use crate::{EPWFile, WeatherData};
+use chrono::{DateTime, Datelike, FixedOffset, TimeDelta};
+use std::fmt;
+
+/// Minimum length in hours of a run of identical values reported as a stuck sensor
+pub const STUCK_SENSOR_HOURS: usize = 24;
+
+/// Fields checked for stuck sensors
+const STUCK_SENSOR_FIELDS: [&str; 6] = [
+    "dry_bulb_temperature",
+    "dew_point_temperature",
+    "relative_humidity",
+    "atmospheric_pressure",
+    "wind_direction",
+    "wind_speed",
+];
+
+/// Valid range of the fields that have one
+#[rustfmt::skip]
+const VALID_RANGES: [(&str, f64, f64); 20] = [
+    ("dry_bulb_temperature", -70., 70.),
+    ("dew_point_temperature", -70., 70.),
+    ("relative_humidity", 0., 100.),
+    ("atmospheric_pressure", 31000., 120000.),
+    ("extraterrestrial_horizontal_radiation", 0., f64::INFINITY),
+    ("extraterrestrial_direct_normal_radiation", 0., f64::INFINITY),
+    ("horizontal_infrared_radiation_intensity", 0., f64::INFINITY),
+    ("global_horizontal_radiation", 0., f64::INFINITY),
+    ("direct_normal_radiation", 0., f64::INFINITY),
+    ("diffuse_horizontal_radiation", 0., f64::INFINITY),
+    ("global_horizontal_illuminance", 0., f64::INFINITY),
+    ("direct_normal_illuminance", 0., f64::INFINITY),
+    ("diffuse_horizontal_illuminance", 0., f64::INFINITY),
+    ("zenith_luminance", 0., f64::INFINITY),
+    ("wind_direction", 0., 360.),
+    ("wind_speed", 0., 40.),
+    ("total_sky_cover", 0., 10.),
+    ("opaque_sky_cover", 0., 10.),
+    ("liquid_precipitation_depth", 0., f64::INFINITY),
+    ("albedo", 0., 1.),
+];
+
+/// Missing values of a column
+#[derive(Debug, PartialEq)]
+pub struct MissingValues {
+    /// Field name of the column in [WeatherData]
+    pub field: &'static str,
+    pub total: usize,
+    /// Missing values in each calendar month, January first
+    pub monthly: [usize; 12],
+}
+
+/// Records missing between two consecutive records
+#[derive(Debug, PartialEq)]
+pub struct MissingRecords {
+    /// Index of the record after the missing ones
+    pub index: usize,
+    /// Timestamp of the first missing record
+    pub timestamp: DateTime<FixedOffset>,
+    pub count: usize,
+}
+
+/// A run of identical values
+#[derive(Debug, PartialEq)]
+pub struct StuckValues {
+    pub field: &'static str,
+    /// Index of the first record of the run
+    pub start: usize,
+    /// Number of records in the run
+    pub length: usize,
+    pub timestamp: DateTime<FixedOffset>,
+    pub value: f64,
+}
+
+/// A value outside the valid range of its field
+#[derive(Debug, PartialEq)]
+pub struct OutOfRangeValue {
+    pub field: &'static str,
+    pub index: usize,
+    pub timestamp: DateTime<FixedOffset>,
+    pub value: f64,
+}
+
+/// Completeness and quality of the weather data
+#[derive(Debug)]
+pub struct QualityReport {
+    pub record_count: usize,
+    /// Missing values of every numeric column
+    pub missing_values: Vec<MissingValues>,
+    /// Indices of the records with the same timestamp as the latest earlier record
+    pub duplicate_timestamps: Vec<usize>,
+    /// Indices of the records with a timestamp before the latest earlier record's
+    pub out_of_order_timestamps: Vec<usize>,
+    pub missing_records: Vec<MissingRecords>,
+    pub stuck_values: Vec<StuckValues>,
+    pub out_of_range_values: Vec<OutOfRangeValue>,
+}
+
+impl QualityReport {
+    /// Report of data without records
+    fn _empty(missing_values: Vec<MissingValues>) -> QualityReport {
+        QualityReport {
+            record_count: 0,
+            missing_values,
+            duplicate_timestamps: Vec::new(),
+            out_of_order_timestamps: Vec::new(),
+            missing_records: Vec::new(),
+            stuck_values: Vec::new(),
+            out_of_range_values: Vec::new(),
+        }
+    }
+
+    /// Whether no problem was found, columns that are entirely missing are not a problem
+    pub fn is_clean(&self) -> bool {
+        self.missing_values
+            .iter()
+            .all(|missing| missing.total == 0 || missing.total == self.record_count)
+            && self.duplicate_timestamps.is_empty()
+            && self.out_of_order_timestamps.is_empty()
+            && self.missing_records.is_empty()
+            && self.stuck_values.is_empty()
+            && self.out_of_range_values.is_empty()
+    }
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Records: {}", self.record_count)?;
+
+        writeln!(f, "Missing values:")?;
+        for missing in self.missing_values.iter().filter(|m| m.total > 0) {
+            if missing.total == self.record_count {
+                writeln!(f, "  {}: not present", missing.field)?;
+                continue;
+            }
+            let months: Vec<String> = missing
+                .monthly
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(|(month, count)| format!("{}: {}", month + 1, count))
+                .collect();
+            writeln!(
+                f,
+                "  {}: {} ({:.1}%), by month {}",
+                missing.field,
+                missing.total,
+                100. * missing.total as f64 / self.record_count.max(1) as f64,
+                months.join(", ")
+            )?;
+        }
+
+        writeln!(
+            f,
+            "Duplicate timestamps: {}",
+            self.duplicate_timestamps.len()
+        )?;
+        writeln!(
+            f,
+            "Out of order timestamps: {}",
+            self.out_of_order_timestamps.len()
+        )?;
+        writeln!(
+            f,
+            "Missing records: {}",
+            self.missing_records.iter().map(|m| m.count).sum::<usize>()
+        )?;
+        for missing in &self.missing_records {
+            writeln!(f, "  {} from {}", missing.count, missing.timestamp)?;
+        }
+        writeln!(f, "Stuck values: {}", self.stuck_values.len())?;
+        for stuck in &self.stuck_values {
+            writeln!(
+                f,
+                "  {}: {} for {} records from {}",
+                stuck.field, stuck.value, stuck.length, stuck.timestamp
+            )?;
+        }
+        write!(f, "Out of range values: {}", self.out_of_range_values.len())?;
+        for value in &self.out_of_range_values {
+            write!(
+                f,
+                "\n  {}: {} at {}",
+                value.field, value.value, value.timestamp
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl WeatherData {
+    /// Check the completeness and quality of the data
+    ///
+    /// ## Parameters
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// The quality report
+    pub fn quality_report(&self, records_per_hour: usize) -> QualityReport {
+        let records_per_hour = records_per_hour.max(1);
+        let months: Vec<usize> = self.timestamp.iter().map(|t| t.month0() as usize).collect();
+
+        let mut missing_values: Vec<MissingValues> = Vec::new();
+        let mut stuck_values: Vec<StuckValues> = Vec::new();
+        let mut out_of_range_values: Vec<OutOfRangeValue> = Vec::new();
+        for (field, values) in self.numeric_columns() {
+            let mut missing = MissingValues {
+                field,
+                total: 0,
+                monthly: [0; 12],
+            };
+            for (value, month) in values.iter().zip(&months) {
+                if value.is_nan() {
+                    missing.total += 1;
+                    missing.monthly[*month] += 1;
+                }
+            }
+            missing_values.push(missing);
+
+            if STUCK_SENSOR_FIELDS.contains(&field) {
+                stuck_values.extend(self._stuck_values(
+                    field,
+                    values,
+                    STUCK_SENSOR_HOURS * records_per_hour,
+                ));
+            }
+
+            if let Some((_, minimum, maximum)) = VALID_RANGES.iter().find(|r| r.0 == field) {
+                out_of_range_values.extend(
+                    values
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, v)| **v < *minimum || **v > *maximum)
+                        .map(|(index, value)| OutOfRangeValue {
+                            field,
+                            index,
+                            timestamp: self.timestamp[index],
+                            value: *value,
+                        }),
+                );
+            }
+        }
+
+        let interval = TimeDelta::seconds(3600 / records_per_hour as i64);
+        let mut duplicate_timestamps: Vec<usize> = Vec::new();
+        let mut out_of_order_timestamps: Vec<usize> = Vec::new();
+        let mut missing_records: Vec<MissingRecords> = Vec::new();
+        // Latest timestamp so far, so that a single misplaced record is the only one reported
+        let mut latest = match self.timestamp.first() {
+            Some(timestamp) => *timestamp,
+            None => return QualityReport::_empty(missing_values),
+        };
+        for index in 1..self.timestamp.len() {
+            let step = _calendar_step(latest, self.timestamp[index]);
+            if step == TimeDelta::zero() {
+                duplicate_timestamps.push(index);
+                continue;
+            } else if step < TimeDelta::zero() {
+                out_of_order_timestamps.push(index);
+                continue;
+            } else if step > interval {
+                missing_records.push(MissingRecords {
+                    index,
+                    timestamp: latest + interval,
+                    count: (step.num_seconds() / interval.num_seconds()) as usize - 1,
+                });
+            }
+            latest = self.timestamp[index];
+        }
+
+        QualityReport {
+            record_count: self.timestamp.len(),
+            missing_values,
+            duplicate_timestamps,
+            out_of_order_timestamps,
+            missing_records,
+            stuck_values,
+            out_of_range_values,
+        }
+    }
+
+    /// Runs of at least `min_length` identical values
+    fn _stuck_values(
+        &self,
+        field: &'static str,
+        values: &[f64],
+        min_length: usize,
+    ) -> Vec<StuckValues> {
+        let mut runs: Vec<StuckValues> = Vec::new();
+        let mut start = 0;
+        for index in 1..=values.len() {
+            if index < values.len() && values[index] == values[start] {
+                continue;
+            }
+            if index - start >= min_length && !values[start].is_nan() {
+                runs.push(StuckValues {
+                    field,
+                    start,
+                    length: index - start,
+                    timestamp: self.timestamp[start],
+                    value: values[start],
+                });
+            }
+            start = index;
+        }
+        runs
+    }
+}
+
+impl EPWFile {
+    /// Check the completeness and quality of the weather data
+    ///
+    /// ## Returns
+    /// The quality report
+    pub fn quality_report(&self) -> QualityReport {
+        self.data
+            .quality_report(self.header.data_periods.records_per_hour)
+    }
+}
+
+/// Time from one timestamp to the next, ignoring a change of year unless the next timestamp would
+/// then be before the previous one
+fn _calendar_step(previous: DateTime<FixedOffset>, next: DateTime<FixedOffset>) -> TimeDelta {
+    if previous.year() == next.year() {
+        return next - previous;
+    }
+    match next.with_year(previous.year()) {
+        Some(same_year) if same_year > previous => same_year - previous,
+        _ => next - previous,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_report() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let report = epw.quality_report();
+        assert_eq!(8760, report.record_count);
+        assert!(report.duplicate_timestamps.is_empty());
+        assert!(report.out_of_order_timestamps.is_empty());
+        // The months of a typical year come from different years
+        assert!(report.missing_records.is_empty());
+        assert!(report.out_of_range_values.is_empty());
+        // The dew point is constant for 26 hours in March
+        assert_eq!(1, report.stuck_values.len());
+        assert_eq!("dew_point_temperature", report.stuck_values[0].field);
+        assert_eq!(26, report.stuck_values[0].length);
+        let albedo = report
+            .missing_values
+            .iter()
+            .find(|m| m.field == "albedo")
+            .unwrap();
+        assert_eq!(744, albedo.monthly[0]);
+        assert!(report.to_string().contains("albedo: not present"));
+
+        let mut indices: Vec<usize> = (0..8760).filter(|idx| *idx != 10).collect();
+        indices.insert(20, 20);
+        indices.insert(40, 5);
+        let mut data = epw.data.select_indices(&indices);
+        data.relative_humidity[30] = 120.;
+        data.dry_bulb_temperature[2] = f64::NAN;
+        for idx in 100..130 {
+            data.dry_bulb_temperature[idx] = 21.;
+        }
+        epw.data = data;
+
+        let report = epw.quality_report();
+        assert!(!report.is_clean());
+        assert_eq!(vec![20], report.duplicate_timestamps);
+        assert_eq!(vec![40], report.out_of_order_timestamps);
+        assert_eq!(1, report.missing_records.len());
+        assert_eq!(
+            epw.data.timestamp[9] + TimeDelta::hours(1),
+            report.missing_records[0].timestamp
+        );
+        let dry_bulb = &report.missing_values[0];
+        assert_eq!((1, 1), (dry_bulb.total, dry_bulb.monthly[0]));
+        assert_eq!(2, report.stuck_values.len());
+        assert_eq!(
+            (100, 30, 21.),
+            (
+                report.stuck_values[0].start,
+                report.stuck_values[0].length,
+                report.stuck_values[0].value
+            )
+        );
+        assert_eq!(1, report.out_of_range_values.len());
+        assert_eq!("relative_humidity", report.out_of_range_values[0].field);
+        let text = report.to_string();
+        assert!(text.contains("Duplicate timestamps: 1"));
+        assert!(text.contains("relative_humidity: 120"));
+    }
+}
@@ -0,0 +1,446 @@
+/*!
+Resampling of the weather data to daily and monthly values, and to sub-hourly intervals.
+
+## Aggregation
+
+Records are grouped by day, or by month, in file order and each group becomes a single record
+with the timestamp, flags and present weather of its first record. The numeric fields are
+aggregated according to their meaning:
+- The radiation fields, in Wh/m², are summed into the energy received over the day or month
+- The liquid precipitation depth is summed
+- The wind direction is the vector mean of the directions weighted by the wind speeds
+- The days since last snowfall and the liquid precipitation quantity, the length in hours of the
+  period the depth was measured over, are the values of the last record
+- The other fields, temperatures, humidity, pressure, illuminances, wind speed, sky cover and so
+  on, are averaged
+
+Missing values are skipped, and a field without any value in a group is NaN.
+
+## Upsampling
+
+Each record is split into records of the requested interval, keeping its flags and present
+weather. Following the EnergyPlus convention, the values of instantaneous fields are reached at
+the end of the record's interval and are interpolated linearly from the previous record's values,
+so the last sub-interval of a record has the record's value. The wind direction is interpolated
+along the shortest arc.
+
+The irradiance, illuminance and zenith luminance of a record are distributed over its
+sub-intervals in proportion to the cosine of the solar zenith angle at the middle of each one,
+keeping their mean over the record equal to the record's value. The extraterrestrial radiation is
+recomputed for the new intervals. The precipitation depth is split evenly, and the days since last
+snowfall and the precipitation quantity are repeated.
+
+The new interval must divide the current one, e.g. 30 minute records can't be split into 10
+minute records, otherwise an [IncompatibleIntervalError] is returned.
+
+*/
+use crate::header::Location;
+use crate::solar::solar_position;
+use crate::{EPWFile, WeatherData};
+use chrono::{DateTime, Datelike, FixedOffset, TimeDelta};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AggregationPeriod {
+    Daily,
+    Monthly,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SubHourlyInterval {
+    FifteenMinutes,
+    TenMinutes,
+    FiveMinutes,
+}
+
+impl SubHourlyInterval {
+    /// Number of records per hour
+    pub fn records_per_hour(&self) -> usize {
+        match self {
+            SubHourlyInterval::FifteenMinutes => 4,
+            SubHourlyInterval::TenMinutes => 6,
+            SubHourlyInterval::FiveMinutes => 12,
+        }
+    }
+}
+
+/// Error returned when the new interval doesn't divide the interval of the records
+#[derive(Debug, PartialEq)]
+pub struct IncompatibleIntervalError {
+    pub records_per_hour: usize,
+    pub target_records_per_hour: usize,
+}
+
+impl fmt::Display for IncompatibleIntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Can't upsample {} records per hour to {} records per hour",
+            self.records_per_hour, self.target_records_per_hour
+        )
+    }
+}
+
+/// How a numeric field is aggregated
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Aggregation {
+    Mean,
+    /// Sum of the values times the interval length in hours
+    Energy,
+    Sum,
+    Last,
+}
+
+/// How a numeric field is upsampled
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Interpolation {
+    Linear,
+    Solar,
+    Split,
+    Repeat,
+}
+
+const RADIATION_FIELDS: [&str; 6] = [
+    "extraterrestrial_horizontal_radiation",
+    "extraterrestrial_direct_normal_radiation",
+    "horizontal_infrared_radiation_intensity",
+    "global_horizontal_radiation",
+    "direct_normal_radiation",
+    "diffuse_horizontal_radiation",
+];
+
+const SOLAR_FIELDS: [&str; 7] = [
+    "global_horizontal_radiation",
+    "direct_normal_radiation",
+    "diffuse_horizontal_radiation",
+    "global_horizontal_illuminance",
+    "direct_normal_illuminance",
+    "diffuse_horizontal_illuminance",
+    "zenith_luminance",
+];
+
+/// Fields describing the state at the end of the record rather than a quantity over it
+const STATE_FIELDS: [&str; 2] = ["days_since_last_snowfall", "liquid_precipitation_quantity"];
+
+fn _aggregation(field: &str) -> Aggregation {
+    if RADIATION_FIELDS.contains(&field) {
+        Aggregation::Energy
+    } else if field == "liquid_precipitation_depth" {
+        Aggregation::Sum
+    } else if STATE_FIELDS.contains(&field) {
+        Aggregation::Last
+    } else {
+        Aggregation::Mean
+    }
+}
+
+fn _interpolation(field: &str) -> Interpolation {
+    if SOLAR_FIELDS.contains(&field) {
+        Interpolation::Solar
+    } else if field == "liquid_precipitation_depth" {
+        Interpolation::Split
+    } else if STATE_FIELDS.contains(&field) {
+        Interpolation::Repeat
+    } else {
+        Interpolation::Linear
+    }
+}
+
+impl WeatherData {
+    /// Aggregate the records into daily or monthly records
+    ///
+    /// ## Parameters
+    /// - `period`: Period of the aggregated records
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// A new WeatherData with one record per day or month
+    pub fn aggregate(&self, period: AggregationPeriod, records_per_hour: usize) -> WeatherData {
+        let key = |timestamp: &DateTime<FixedOffset>| match period {
+            AggregationPeriod::Daily => (timestamp.year(), timestamp.month(), timestamp.day()),
+            AggregationPeriod::Monthly => (timestamp.year(), timestamp.month(), 1),
+        };
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (idx, timestamp) in self.timestamp.iter().enumerate() {
+            match groups.last_mut() {
+                Some(group) if key(&self.timestamp[group[0]]) == key(timestamp) => group.push(idx),
+                _ => groups.push(vec![idx]),
+            }
+        }
+
+        let first_indices: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+        let mut aggregated = self.select_indices(&first_indices);
+        let hours_per_record = 1. / records_per_hour.max(1) as f64;
+        for ((field, values), (_, dest)) in self
+            .numeric_columns()
+            .into_iter()
+            .zip(aggregated.numeric_columns_mut())
+        {
+            let rule = _aggregation(field);
+            for (value, group) in dest.iter_mut().zip(&groups) {
+                let known: Vec<f64> = group
+                    .iter()
+                    .map(|idx| values[*idx])
+                    .filter(|v| !v.is_nan())
+                    .collect();
+                *value = match (rule, known.is_empty()) {
+                    (_, true) => f64::NAN,
+                    (Aggregation::Mean, false) => known.iter().sum::<f64>() / known.len() as f64,
+                    (Aggregation::Energy, false) => known.iter().sum::<f64>() * hours_per_record,
+                    (Aggregation::Sum, false) => known.iter().sum(),
+                    (Aggregation::Last, false) => known[known.len() - 1],
+                };
+            }
+        }
+        aggregated.wind_direction = groups
+            .iter()
+            .map(|group| self._vector_mean_wind_direction(group))
+            .collect();
+        aggregated
+    }
+
+    /// Split the records into records of a shorter interval
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station, used for the position of the sun
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    /// - `interval`: Interval of the new records
+    ///
+    /// ## Returns
+    /// A new WeatherData with the shorter interval, or an IncompatibleIntervalError when the new
+    /// interval doesn't divide the current one
+    pub fn upsample(
+        &self,
+        location: &Location,
+        records_per_hour: usize,
+        interval: SubHourlyInterval,
+    ) -> Result<WeatherData, IncompatibleIntervalError> {
+        let target = interval.records_per_hour();
+        if records_per_hour == 0 || target % records_per_hour != 0 {
+            return Err(IncompatibleIntervalError {
+                records_per_hour,
+                target_records_per_hour: target,
+            });
+        }
+        let factor = target / records_per_hour;
+        let step = TimeDelta::seconds(3600 / target as i64);
+
+        let indices: Vec<usize> = (0..self.len())
+            .flat_map(|idx| std::iter::repeat(idx).take(factor))
+            .collect();
+        let mut upsampled = self.select_indices(&indices);
+        for (idx, timestamp) in upsampled.timestamp.iter_mut().enumerate() {
+            *timestamp += step * (idx % factor) as i32;
+        }
+
+        // Cosine of the zenith angle at the middle of each new record, at least 0
+        let cos_zenith: Vec<f64> = upsampled
+            .timestamp
+            .iter()
+            .map(|timestamp| {
+                let sun = solar_position(
+                    location.latitude,
+                    location.longitude,
+                    timestamp.with_timezone(&location.time_zone) + step / 2,
+                );
+                sun.zenith.to_radians().cos().max(0.)
+            })
+            .collect();
+
+        for ((field, values), (_, dest)) in self
+            .numeric_columns()
+            .into_iter()
+            .zip(upsampled.numeric_columns_mut())
+        {
+            let rule = _interpolation(field);
+            for (idx, value) in values.iter().enumerate() {
+                let previous = match idx {
+                    0 => *value,
+                    _ => values[idx - 1],
+                };
+                let sub_cos_zenith = &cos_zenith[idx * factor..(idx + 1) * factor];
+                let mean_cos_zenith = sub_cos_zenith.iter().sum::<f64>() / factor as f64;
+                for k in 0..factor {
+                    let fraction = (k + 1) as f64 / factor as f64;
+                    dest[idx * factor + k] = match rule {
+                        Interpolation::Linear if field == "wind_direction" => {
+                            _interpolate_direction(previous, *value, fraction)
+                        }
+                        Interpolation::Linear => match previous.is_nan() {
+                            true => *value,
+                            false => previous + (value - previous) * fraction,
+                        },
+                        Interpolation::Solar => match mean_cos_zenith > 0. {
+                            true => value * sub_cos_zenith[k] / mean_cos_zenith,
+                            false => *value,
+                        },
+                        Interpolation::Split => value / factor as f64,
+                        Interpolation::Repeat => *value,
+                    };
+                }
+            }
+        }
+
+        let extraterrestrial = upsampled.extraterrestrial_radiation(location, target);
+        upsampled.extraterrestrial_horizontal_radiation = extraterrestrial.horizontal;
+        upsampled.extraterrestrial_direct_normal_radiation = extraterrestrial.direct_normal;
+        Ok(upsampled)
+    }
+
+    /// Direction of the mean wind vector of some records, weighted by the wind speeds
+    fn _vector_mean_wind_direction(&self, indices: &[usize]) -> f64 {
+        let mut east = 0.;
+        let mut north = 0.;
+        let mut unweighted_east = 0.;
+        let mut unweighted_north = 0.;
+        for idx in indices {
+            let direction = self.wind_direction[*idx].to_radians();
+            if direction.is_nan() {
+                continue;
+            }
+            let speed = match self.wind_speed[*idx].is_nan() {
+                true => 0.,
+                false => self.wind_speed[*idx],
+            };
+            east += speed * direction.sin();
+            north += speed * direction.cos();
+            unweighted_east += direction.sin();
+            unweighted_north += direction.cos();
+        }
+        // Fall back to the unweighted mean when all the records are calm
+        if east.hypot(north) < 1e-9 {
+            east = unweighted_east;
+            north = unweighted_north;
+        }
+        match east.hypot(north) < 1e-9 {
+            true => f64::NAN,
+            false => east.atan2(north).to_degrees().rem_euclid(360.),
+        }
+    }
+}
+
+impl EPWFile {
+    /// Aggregate the records into daily or monthly records
+    ///
+    /// ## Parameters
+    /// - `period`: Period of the aggregated records
+    ///
+    /// ## Returns
+    /// A new WeatherData with one record per day or month
+    pub fn aggregate(&self, period: AggregationPeriod) -> WeatherData {
+        self.data
+            .aggregate(period, self.header.data_periods.records_per_hour)
+    }
+
+    /// Split the records into records of a shorter interval, and update the number of records
+    /// per hour of the header
+    ///
+    /// ## Parameters
+    /// - `interval`: Interval of the new records
+    ///
+    /// ## Returns
+    /// An IncompatibleIntervalError when the new interval doesn't divide the current one
+    pub fn upsample(
+        &mut self,
+        interval: SubHourlyInterval,
+    ) -> Result<(), IncompatibleIntervalError> {
+        self.data = self.data.upsample(
+            &self.header.location,
+            self.header.data_periods.records_per_hour,
+            interval,
+        )?;
+        self.header.data_periods.records_per_hour = interval.records_per_hour();
+        Ok(())
+    }
+}
+
+/// Interpolate between two directions in degrees along the shortest arc
+fn _interpolate_direction(from: f64, to: f64, fraction: f64) -> f64 {
+    if from.is_nan() || to.is_nan() {
+        return to;
+    }
+    let difference = (to - from + 180.).rem_euclid(360.) - 180.;
+    (from + difference * fraction).rem_euclid(360.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+
+        let daily = epw.aggregate(AggregationPeriod::Daily);
+        assert_eq!(365, daily.len());
+        assert_eq!(epw.data.timestamp[24], daily.timestamp[1]);
+        let first_day = &epw.data.global_horizontal_radiation[..24];
+        assert_eq!(
+            first_day.iter().sum::<f64>(),
+            daily.global_horizontal_radiation[0]
+        );
+        let mean = epw.data.dry_bulb_temperature[..24].iter().sum::<f64>() / 24.;
+        assert!((mean - daily.dry_bulb_temperature[0]).abs() < 1e-9);
+        let monthly = epw.aggregate(AggregationPeriod::Monthly);
+        assert_eq!(12, monthly.len());
+        assert!((0. ..360.).contains(&monthly.wind_direction[0]));
+
+        // The precipitation quantity is the measurement period, not an amount
+        let mut rainy = epw.data.clone();
+        rainy.liquid_precipitation_depth = vec![0.5; 8760];
+        rainy.liquid_precipitation_quantity = vec![1.; 8760];
+        let daily_rain = rainy.aggregate(AggregationPeriod::Daily, 1);
+        assert_eq!(
+            (12., 1.),
+            (
+                daily_rain.liquid_precipitation_depth[0],
+                daily_rain.liquid_precipitation_quantity[0]
+            )
+        );
+        let location = &epw.header.location;
+        let upsampled = rainy
+            .upsample(location, 1, SubHourlyInterval::FifteenMinutes)
+            .unwrap();
+        assert_eq!(
+            (0.125, 1.),
+            (
+                upsampled.liquid_precipitation_depth[0],
+                upsampled.liquid_precipitation_quantity[0]
+            )
+        );
+
+        let hourly = epw.data.clone();
+        epw.upsample(SubHourlyInterval::FifteenMinutes).unwrap();
+        assert_eq!(4, epw.header.data_periods.records_per_hour);
+        assert_eq!(4 * 8760, epw.data.len());
+        assert_eq!(hourly.timestamp[1], epw.data.timestamp[4]);
+        assert_eq!(
+            TimeDelta::minutes(15),
+            epw.data.timestamp[5] - epw.data.timestamp[4]
+        );
+        assert_eq!(hourly.flags[1], epw.data.flags[7]);
+
+        // Temperatures reach the hourly values at the end of each hour
+        assert_eq!(
+            hourly.dry_bulb_temperature[13],
+            epw.data.dry_bulb_temperature[55]
+        );
+        let midpoint = (hourly.dry_bulb_temperature[12] + hourly.dry_bulb_temperature[13]) / 2.;
+        assert!((midpoint - epw.data.dry_bulb_temperature[53]).abs() < 1e-9);
+
+        // Irradiance keeps its hourly mean and follows the sun
+        let sub_hourly = &epw.data.global_horizontal_radiation[4 * 8..4 * 9];
+        let mean = sub_hourly.iter().sum::<f64>() / 4.;
+        assert!((hourly.global_horizontal_radiation[8] - mean).abs() < 1e-9);
+        assert!(sub_hourly[0] < sub_hourly[3]);
+
+        assert_eq!(
+            Err(IncompatibleIntervalError {
+                records_per_hour: 4,
+                target_records_per_hour: 6
+            }),
+            epw.upsample(SubHourlyInterval::TenMinutes)
+        );
+        assert!(_interpolate_direction(350., 10., 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,305 @@
+/*!
+Selection of a subset of the records of [WeatherData].
+
+Every selection returns a new [WeatherData] with all the columns, including the flags and the
+present weather codes, restricted to the same records in their original order. The selections are
+built on [WeatherData::select_indices], which can also be used directly with any list of record
+indices.
+
+The calendar selectors use the timestamp of each record, which marks the start of its interval,
+so the record of the hour ending at 1:00 is in hour 0.
+
+*/
+use crate::weather_data::PresentWeather;
+use crate::WeatherData;
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+
+/// The values of a single record, see [WeatherData] for the units
+#[derive(Debug, PartialEq)]
+pub struct WeatherRecord<'a> {
+    pub timestamp: DateTime<FixedOffset>,
+    pub flags: &'a str,
+    pub dry_bulb_temperature: f64,
+    pub dew_point_temperature: f64,
+    pub relative_humidity: f64,
+    pub atmospheric_pressure: f64,
+    pub extraterrestrial_horizontal_radiation: f64,
+    pub extraterrestrial_direct_normal_radiation: f64,
+    pub horizontal_infrared_radiation_intensity: f64,
+    pub global_horizontal_radiation: f64,
+    pub direct_normal_radiation: f64,
+    pub diffuse_horizontal_radiation: f64,
+    pub global_horizontal_illuminance: f64,
+    pub direct_normal_illuminance: f64,
+    pub diffuse_horizontal_illuminance: f64,
+    pub zenith_luminance: f64,
+    pub wind_direction: f64,
+    pub wind_speed: f64,
+    pub total_sky_cover: f64,
+    pub opaque_sky_cover: f64,
+    pub visibility: f64,
+    pub ceiling_height: f64,
+    pub present_weather_observation: bool,
+    pub present_weather_codes: &'a PresentWeather,
+    pub precipitable_water: f64,
+    pub aerosol_optical_depth: f64,
+    pub snow_depth: f64,
+    pub days_since_last_snowfall: f64,
+    pub albedo: f64,
+    pub liquid_precipitation_depth: f64,
+    pub liquid_precipitation_quantity: f64,
+}
+
+impl WeatherData {
+    /// Number of records
+    pub fn len(&self) -> usize {
+        self.timestamp.len()
+    }
+
+    /// Whether there are no records
+    pub fn is_empty(&self) -> bool {
+        self.timestamp.is_empty()
+    }
+
+    /// The values of the record at an index
+    ///
+    /// ## Parameters
+    /// - `index`: Index of the record, panics when out of bounds
+    ///
+    /// ## Returns
+    /// The record
+    pub fn record(&self, index: usize) -> WeatherRecord<'_> {
+        WeatherRecord {
+            timestamp: self.timestamp[index],
+            flags: &self.flags[index],
+            dry_bulb_temperature: self.dry_bulb_temperature[index],
+            dew_point_temperature: self.dew_point_temperature[index],
+            relative_humidity: self.relative_humidity[index],
+            atmospheric_pressure: self.atmospheric_pressure[index],
+            extraterrestrial_horizontal_radiation: self.extraterrestrial_horizontal_radiation
+                [index],
+            extraterrestrial_direct_normal_radiation: self.extraterrestrial_direct_normal_radiation
+                [index],
+            horizontal_infrared_radiation_intensity: self.horizontal_infrared_radiation_intensity
+                [index],
+            global_horizontal_radiation: self.global_horizontal_radiation[index],
+            direct_normal_radiation: self.direct_normal_radiation[index],
+            diffuse_horizontal_radiation: self.diffuse_horizontal_radiation[index],
+            global_horizontal_illuminance: self.global_horizontal_illuminance[index],
+            direct_normal_illuminance: self.direct_normal_illuminance[index],
+            diffuse_horizontal_illuminance: self.diffuse_horizontal_illuminance[index],
+            zenith_luminance: self.zenith_luminance[index],
+            wind_direction: self.wind_direction[index],
+            wind_speed: self.wind_speed[index],
+            total_sky_cover: self.total_sky_cover[index],
+            opaque_sky_cover: self.opaque_sky_cover[index],
+            visibility: self.visibility[index],
+            ceiling_height: self.ceiling_height[index],
+            present_weather_observation: self.present_weather_observation[index],
+            present_weather_codes: &self.present_weather_codes[index],
+            precipitable_water: self.precipitable_water[index],
+            aerosol_optical_depth: self.aerosol_optical_depth[index],
+            snow_depth: self.snow_depth[index],
+            days_since_last_snowfall: self.days_since_last_snowfall[index],
+            albedo: self.albedo[index],
+            liquid_precipitation_depth: self.liquid_precipitation_depth[index],
+            liquid_precipitation_quantity: self.liquid_precipitation_quantity[index],
+        }
+    }
+
+    /// Iterate over the records
+    pub fn records(&self) -> impl Iterator<Item = WeatherRecord<'_>> {
+        (0..self.len()).map(|index| self.record(index))
+    }
+
+    /// Select records by index
+    ///
+    /// ## Parameters
+    /// - `indices`: Indices of the records to keep, in the order they should appear. Panics when
+    ///   an index is out of bounds.
+    ///
+    /// ## Returns
+    /// A new WeatherData with the selected records
+    pub fn select_indices(&self, indices: &[usize]) -> WeatherData {
+        WeatherData {
+            timestamp: _pick(&self.timestamp, indices),
+            flags: _pick(&self.flags, indices),
+            dry_bulb_temperature: _pick(&self.dry_bulb_temperature, indices),
+            dew_point_temperature: _pick(&self.dew_point_temperature, indices),
+            relative_humidity: _pick(&self.relative_humidity, indices),
+            atmospheric_pressure: _pick(&self.atmospheric_pressure, indices),
+            extraterrestrial_horizontal_radiation: _pick(
+                &self.extraterrestrial_horizontal_radiation,
+                indices,
+            ),
+            extraterrestrial_direct_normal_radiation: _pick(
+                &self.extraterrestrial_direct_normal_radiation,
+                indices,
+            ),
+            horizontal_infrared_radiation_intensity: _pick(
+                &self.horizontal_infrared_radiation_intensity,
+                indices,
+            ),
+            global_horizontal_radiation: _pick(&self.global_horizontal_radiation, indices),
+            direct_normal_radiation: _pick(&self.direct_normal_radiation, indices),
+            diffuse_horizontal_radiation: _pick(&self.diffuse_horizontal_radiation, indices),
+            global_horizontal_illuminance: _pick(&self.global_horizontal_illuminance, indices),
+            direct_normal_illuminance: _pick(&self.direct_normal_illuminance, indices),
+            diffuse_horizontal_illuminance: _pick(&self.diffuse_horizontal_illuminance, indices),
+            zenith_luminance: _pick(&self.zenith_luminance, indices),
+            wind_direction: _pick(&self.wind_direction, indices),
+            wind_speed: _pick(&self.wind_speed, indices),
+            total_sky_cover: _pick(&self.total_sky_cover, indices),
+            opaque_sky_cover: _pick(&self.opaque_sky_cover, indices),
+            visibility: _pick(&self.visibility, indices),
+            ceiling_height: _pick(&self.ceiling_height, indices),
+            present_weather_observation: _pick(&self.present_weather_observation, indices),
+            present_weather_codes: _pick(&self.present_weather_codes, indices),
+            precipitable_water: _pick(&self.precipitable_water, indices),
+            aerosol_optical_depth: _pick(&self.aerosol_optical_depth, indices),
+            snow_depth: _pick(&self.snow_depth, indices),
+            days_since_last_snowfall: _pick(&self.days_since_last_snowfall, indices),
+            albedo: _pick(&self.albedo, indices),
+            liquid_precipitation_depth: _pick(&self.liquid_precipitation_depth, indices),
+            liquid_precipitation_quantity: _pick(&self.liquid_precipitation_quantity, indices),
+        }
+    }
+
+    /// Select the records for which a predicate is true
+    ///
+    /// ## Parameters
+    /// - `predicate`: Returns true for the records to keep, e.g.
+    ///   `|record| record.dry_bulb_temperature > 30.`
+    ///
+    /// ## Returns
+    /// A new WeatherData with the selected records
+    pub fn filter<F: Fn(&WeatherRecord) -> bool>(&self, predicate: F) -> WeatherData {
+        let indices: Vec<usize> = (0..self.len())
+            .filter(|index| predicate(&self.record(*index)))
+            .collect();
+        self.select_indices(&indices)
+    }
+
+    /// Select the records with a timestamp in a time range
+    ///
+    /// ## Parameters
+    /// - `start`: First timestamp to keep
+    /// - `end`: Timestamp after the last one to keep
+    ///
+    /// ## Returns
+    /// A new WeatherData with the records from `start` included to `end` excluded
+    pub fn slice_by_time(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> WeatherData {
+        self._select_by_timestamp(|timestamp| *timestamp >= start && *timestamp < end)
+    }
+
+    /// Select the records of some calendar months
+    ///
+    /// ## Parameters
+    /// - `months`: Months to keep, 1 for January
+    ///
+    /// ## Returns
+    /// A new WeatherData with the records of the months
+    pub fn select_months(&self, months: &[u32]) -> WeatherData {
+        self._select_by_timestamp(|timestamp| months.contains(&timestamp.month()))
+    }
+
+    /// Select the records of a range of days of the year
+    ///
+    /// The range wraps around the end of the year when `first` is after `last`, e.g. 335 to 59
+    /// selects December to February of a non-leap year.
+    ///
+    /// ## Parameters
+    /// - `first`: First day of the year to keep, 1 for January 1st
+    /// - `last`: Last day of the year to keep, included
+    ///
+    /// ## Returns
+    /// A new WeatherData with the records of the days
+    pub fn select_days_of_year(&self, first: u32, last: u32) -> WeatherData {
+        self._select_by_timestamp(|timestamp| _in_wrapping_range(timestamp.ordinal(), first, last))
+    }
+
+    /// Select the records of a range of hours of the day
+    ///
+    /// The range wraps around midnight when `first` is after `last`, e.g. 22 to 5 selects the
+    /// night records.
+    ///
+    /// ## Parameters
+    /// - `first`: First hour of the day to keep, 0 to 23
+    /// - `last`: Last hour of the day to keep, included
+    ///
+    /// ## Returns
+    /// A new WeatherData with the records of the hours
+    pub fn select_hours_of_day(&self, first: u32, last: u32) -> WeatherData {
+        self._select_by_timestamp(|timestamp| _in_wrapping_range(timestamp.hour(), first, last))
+    }
+
+    fn _select_by_timestamp<F: Fn(&DateTime<FixedOffset>) -> bool>(&self, keep: F) -> WeatherData {
+        let indices: Vec<usize> = (0..self.len())
+            .filter(|index| keep(&self.timestamp[*index]))
+            .collect();
+        self.select_indices(&indices)
+    }
+}
+
+fn _pick<T: Clone>(values: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|index| values[*index].clone()).collect()
+}
+
+/// Whether a value is in an inclusive range that wraps around when `first` is after `last`
+fn _in_wrapping_range(value: u32, first: u32, last: u32) -> bool {
+    match first <= last {
+        true => value >= first && value <= last,
+        false => value >= first || value <= last,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPWFile;
+
+    #[test]
+    fn test_select_records() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let data = &epw.data;
+        assert_eq!(8760, data.len());
+
+        let selected = data.select_indices(&[5, 2]);
+        assert_eq!(2, selected.len());
+        assert_eq!(data.timestamp[5], selected.record(0).timestamp);
+        assert_eq!(data.wind_speed[5], selected.record(0).wind_speed);
+        assert_eq!(data.flags[2], selected.flags[1]);
+        assert_eq!(
+            data.present_weather_codes[2],
+            selected.present_weather_codes[1]
+        );
+
+        let start = data.timestamp[24];
+        let end = data.timestamp[48];
+        let day = data.slice_by_time(start, end);
+        assert_eq!(24, day.len());
+        assert_eq!(data.dry_bulb_temperature[24..48], day.dry_bulb_temperature);
+
+        let hot = data.filter(|record| record.dry_bulb_temperature > 30.);
+        assert!(!hot.is_empty());
+        assert!(hot
+            .records()
+            .all(|record| record.dry_bulb_temperature > 30.));
+
+        let summer = data.select_months(&[6, 7, 8]);
+        assert_eq!(92 * 24, summer.len());
+        let winter = data.select_days_of_year(335, 59);
+        assert_eq!(90 * 24, winter.len());
+        assert_eq!(data.timestamp[0], winter.timestamp[0]);
+        let night = data.select_hours_of_day(22, 5);
+        assert_eq!(365 * 8, night.len());
+        assert!(night
+            .timestamp
+            .iter()
+            .all(|t| t.hour() >= 22 || t.hour() <= 5));
+    }
+}
@@ -0,0 +1,185 @@
+/*!
+Longwave radiation from the sky and effective sky temperature.
+
+The horizontal infrared radiation is often missing from weather files. EnergyPlus estimates it
+from the sky emissivity, itself a function of the dew point temperature and the opaque sky cover:
+
+```text
+IR = emissivity σ T_db⁴
+emissivity = clear_sky_emissivity (1 + 0.0224 N - 0.0035 N² + 0.00028 N³)
+```
+
+where `T_db` is the dry bulb temperature in Kelvin and `N` is the opaque sky cover in tenths. The
+[SkyEmissivityModel]s give the clear sky emissivity; the cloud cover correction is the one of Clark
+and Allen (1978) for both. When the opaque sky cover is missing the total sky cover is used
+instead.
+
+The effective sky temperature is the temperature of a black body emitting the horizontal infrared
+radiation, `T_sky = (IR / σ)^0.25`.
+
+*/
+use crate::{EPWFile, WeatherData};
+
+/// Stefan-Boltzmann constant in W/m²K⁴, the value used by EnergyPlus
+pub const STEFAN_BOLTZMANN: f64 = 5.6697e-8;
+
+/// Model used to compute the clear sky emissivity
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SkyEmissivityModel {
+    /// `0.787 + 0.764 ln(T_dp / 273)` with the dew point in Kelvin (Clark and Allen, 1978). This
+    /// is the EnergyPlus default.
+    ClarkAllen,
+    /// `0.758 + 0.521 (T_dp / 100) + 0.625 (T_dp / 100)²` with the dew point in °C (Berdahl and
+    /// Martin, 1984)
+    BerdahlMartin,
+}
+
+/// Sky emissivity from the dew point temperature in °C and the opaque sky cover in tenths
+pub fn sky_emissivity(
+    dew_point_temperature: f64,
+    opaque_sky_cover: f64,
+    model: SkyEmissivityModel,
+) -> f64 {
+    let clear_sky = match model {
+        SkyEmissivityModel::ClarkAllen => {
+            0.787 + 0.764 * ((dew_point_temperature + 273.15) / 273.).ln()
+        }
+        SkyEmissivityModel::BerdahlMartin => {
+            let t = dew_point_temperature / 100.;
+            0.758 + 0.521 * t + 0.625 * t.powi(2)
+        }
+    };
+    let n = opaque_sky_cover;
+    clear_sky * (1. + 0.0224 * n - 0.0035 * n.powi(2) + 0.00028 * n.powi(3))
+}
+
+/// Horizontal infrared radiation in W/m² from the dry bulb and dew point temperatures in °C and
+/// the opaque sky cover in tenths
+pub fn horizontal_infrared_radiation(
+    dry_bulb_temperature: f64,
+    dew_point_temperature: f64,
+    opaque_sky_cover: f64,
+    model: SkyEmissivityModel,
+) -> f64 {
+    sky_emissivity(dew_point_temperature, opaque_sky_cover, model)
+        * STEFAN_BOLTZMANN
+        * (dry_bulb_temperature + 273.15).powi(4)
+}
+
+/// Effective sky temperature in °C from the horizontal infrared radiation in W/m²
+pub fn sky_temperature(horizontal_infrared_radiation: f64) -> f64 {
+    (horizontal_infrared_radiation / STEFAN_BOLTZMANN).powf(0.25) - 273.15
+}
+
+impl WeatherData {
+    /// Estimate the horizontal infrared radiation for each record
+    ///
+    /// ## Parameters
+    /// - `model`: Clear sky emissivity model
+    ///
+    /// ## Returns
+    /// The estimated horizontal infrared radiation in Wh/m²
+    pub fn estimate_horizontal_infrared_radiation(&self, model: SkyEmissivityModel) -> Vec<f64> {
+        (0..self.timestamp.len())
+            .map(|idx| {
+                let sky_cover = match self.opaque_sky_cover[idx].is_nan() {
+                    true => self.total_sky_cover[idx],
+                    false => self.opaque_sky_cover[idx],
+                };
+                horizontal_infrared_radiation(
+                    self.dry_bulb_temperature[idx],
+                    self.dew_point_temperature[idx],
+                    sky_cover,
+                    model,
+                )
+            })
+            .collect()
+    }
+
+    /// Effective sky temperature in °C for each record
+    ///
+    /// The horizontal infrared radiation of the record is used when present, otherwise it is
+    /// estimated with the given model.
+    ///
+    /// ## Parameters
+    /// - `model`: Clear sky emissivity model used for the missing infrared radiation values
+    ///
+    /// ## Returns
+    /// The effective sky temperature in °C
+    pub fn sky_temperature(&self, model: SkyEmissivityModel) -> Vec<f64> {
+        let estimated = self.estimate_horizontal_infrared_radiation(model);
+        self.horizontal_infrared_radiation_intensity
+            .iter()
+            .zip(estimated)
+            .map(|(ir, estimate)| match ir.is_nan() {
+                true => sky_temperature(estimate),
+                false => sky_temperature(*ir),
+            })
+            .collect()
+    }
+
+    /// Replace the horizontal infrared radiation with estimated values
+    ///
+    /// ## Parameters
+    /// - `model`: Clear sky emissivity model
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_horizontal_infrared_radiation(
+        &mut self,
+        model: SkyEmissivityModel,
+        only_missing: bool,
+    ) {
+        let estimated = self.estimate_horizontal_infrared_radiation(model);
+        for (ir, estimate) in self
+            .horizontal_infrared_radiation_intensity
+            .iter_mut()
+            .zip(estimated)
+        {
+            if !only_missing || ir.is_nan() {
+                *ir = estimate;
+            }
+        }
+    }
+}
+
+impl EPWFile {
+    /// Replace the horizontal infrared radiation with estimated values
+    ///
+    /// ## Parameters
+    /// - `model`: Clear sky emissivity model
+    /// - `only_missing`: Only replace values that are missing (NaN)
+    pub fn fill_horizontal_infrared_radiation(
+        &mut self,
+        model: SkyEmissivityModel,
+        only_missing: bool,
+    ) {
+        self.data
+            .fill_horizontal_infrared_radiation(model, only_missing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_horizontal_infrared_radiation() {
+        let emissivity = sky_emissivity(10., 0., SkyEmissivityModel::ClarkAllen);
+        assert!((0.8149 - emissivity).abs() < 1e-4);
+        let ir = horizontal_infrared_radiation(20., 10., 0., SkyEmissivityModel::ClarkAllen);
+        assert!((341.2 - ir).abs() < 0.1);
+        assert!(sky_temperature(ir) < 10.);
+        assert!(sky_temperature(STEFAN_BOLTZMANN * 273.15_f64.powi(4)).abs() < 1e-9);
+
+        // The infrared radiation of the test file was computed by the EnergyPlus weather
+        // converter with the Clark and Allen model
+        let mut epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let original = epw.data.horizontal_infrared_radiation_intensity.clone();
+        epw.data.horizontal_infrared_radiation_intensity[10] = f64::NAN;
+        epw.fill_horizontal_infrared_radiation(SkyEmissivityModel::ClarkAllen, true);
+        assert!((original[10] - epw.data.horizontal_infrared_radiation_intensity[10]).abs() < 1.);
+        assert_eq!(
+            original[11],
+            epw.data.horizontal_infrared_radiation_intensity[11]
+        );
+    }
+}
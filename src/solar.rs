@@ -0,0 +1,204 @@
+/*!
+Solar position for a location and time.
+
+The position is computed with the low precision algorithm of the Astronomical Almanac, as
+described by Michalsky (1988), "The Astronomical Almanac's algorithm for approximate solar
+position (1950-2050)". It is accurate to about 0.01° over that period, which is well below the
+resolution of hourly weather data. The altitude is geometric; no correction is made for
+atmospheric refraction.
+
+The values in an EPW file describe the interval ending at the hour of the record, and
+[WeatherData::timestamp] marks the start of that interval, so [WeatherData::solar_position] and
+[EPWFile::solar_position] compute the sun position at the middle of each interval, e.g. 00:30 for
+the first record of an hourly file.
+
+Angles are in degrees. Azimuth is measured clockwise from north, and the hour angle is negative
+in the morning and positive in the afternoon.
+
+*/
+use crate::header::Location;
+use crate::{EPWFile, WeatherData};
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+/// Solar constant in W/m², the value used by the TMY2 and TMY3 data sets
+pub const SOLAR_CONSTANT: f64 = 1367.;
+
+/// Unix timestamp of the J2000.0 epoch, 2000-01-01 12:00 UTC
+const J2000_UNIX_SECONDS: i64 = 946728000;
+
+/// Position of the sun at an instant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    /// Declination of the sun in degrees
+    pub declination: f64,
+    /// Equation of time in minutes, apparent solar time minus mean solar time
+    pub equation_of_time: f64,
+    /// Hour angle in degrees, 0 at solar noon
+    pub hour_angle: f64,
+    /// Zenith angle in degrees
+    pub zenith: f64,
+    /// Altitude above the horizon in degrees, negative when the sun is below the horizon
+    pub altitude: f64,
+    /// Azimuth in degrees clockwise from north
+    pub azimuth: f64,
+}
+
+impl SolarPosition {
+    /// Whether the sun is above the horizon
+    pub fn is_up(&self) -> bool {
+        self.altitude > 0.
+    }
+}
+
+/// Compute the position of the sun
+///
+/// ## Parameters
+/// - `latitude`: Latitude in degrees, positive north
+/// - `longitude`: Longitude in degrees, positive east
+/// - `time`: Instant to compute the position for
+///
+/// ## Returns
+/// The position of the sun
+pub fn solar_position(latitude: f64, longitude: f64, time: DateTime<FixedOffset>) -> SolarPosition {
+    // Days since the J2000.0 epoch, 2000-01-01 12:00 UTC
+    let n = (time.timestamp() - J2000_UNIX_SECONDS) as f64 / 86400.
+        + time.timestamp_subsec_nanos() as f64 / 86400e9;
+
+    let mean_longitude = (280.460 + 0.9856474 * n).rem_euclid(360.);
+    let mean_anomaly = (357.528 + 0.9856003 * n).rem_euclid(360.).to_radians();
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2. * mean_anomaly).sin())
+            .to_radians();
+    let obliquity = (23.439 - 0.0000004 * n).to_radians();
+
+    let right_ascension = (obliquity.cos() * ecliptic_longitude.sin())
+        .atan2(ecliptic_longitude.cos())
+        .to_degrees();
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+    let equation_of_time = 4. * _wrap_degrees(mean_longitude - right_ascension);
+
+    let utc_hours = (n + 0.5).rem_euclid(1.) * 24.;
+    let sidereal_time = 6.697375 + 0.0657098242 * n + utc_hours + longitude / 15.;
+    let hour_angle = _wrap_degrees(sidereal_time * 15. - right_ascension);
+
+    let phi = latitude.to_radians();
+    let h = hour_angle.to_radians();
+    let cos_zenith =
+        (phi.sin() * declination.sin() + phi.cos() * declination.cos() * h.cos()).clamp(-1., 1.);
+    let zenith = cos_zenith.acos().to_degrees();
+    let azimuth = h
+        .sin()
+        .atan2(h.cos() * phi.sin() - declination.tan() * phi.cos())
+        .to_degrees()
+        + 180.;
+
+    SolarPosition {
+        declination: declination.to_degrees(),
+        equation_of_time,
+        hour_angle,
+        zenith,
+        altitude: 90. - zenith,
+        azimuth,
+    }
+}
+
+/// Relative optical air mass at sea level for a zenith angle in degrees (Kasten and Young, 1989)
+pub fn relative_air_mass(zenith: f64) -> f64 {
+    1. / (zenith.to_radians().cos() + 0.50572 * (96.07995 - zenith).powf(-1.6364))
+}
+
+/// Wrap an angle in degrees to [-180, 180)
+fn _wrap_degrees(angle: f64) -> f64 {
+    (angle + 180.).rem_euclid(360.) - 180.
+}
+
+impl WeatherData {
+    /// Compute the position of the sun at the middle of each record's interval
+    ///
+    /// ## Parameters
+    /// - `location`: Location of the weather station
+    /// - `records_per_hour`: Number of records per hour, from [crate::header::DataPeriods]
+    ///
+    /// ## Returns
+    /// The position of the sun for each record
+    pub fn solar_position(
+        &self,
+        location: &Location,
+        records_per_hour: usize,
+    ) -> Vec<SolarPosition> {
+        let half_interval = TimeDelta::seconds(1800 / records_per_hour.max(1) as i64);
+        self.timestamp
+            .iter()
+            .map(|timestamp| {
+                solar_position(
+                    location.latitude,
+                    location.longitude,
+                    timestamp.with_timezone(&location.time_zone) + half_interval,
+                )
+            })
+            .collect()
+    }
+}
+
+impl EPWFile {
+    /// Compute the position of the sun at the middle of each record's interval
+    ///
+    /// ## Returns
+    /// The position of the sun for each record
+    pub fn solar_position(&self) -> Vec<SolarPosition> {
+        self.data.solar_position(
+            &self.header.location,
+            self.header.data_periods.records_per_hour,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn _assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() < tolerance,
+            "expected {} got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_solar_position() {
+        // Golden, CO on 2003-10-17 12:30:30 MST, the example from the NREL SPA report
+        let time = FixedOffset::west_opt(7 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2003, 10, 17, 12, 30, 30)
+            .unwrap();
+        let position = solar_position(39.742476, -105.1786, time);
+        // SPA includes about 0.016° of refraction in its zenith angle
+        _assert_close(50.11162, position.zenith, 0.03);
+        _assert_close(194.34024, position.azimuth, 0.01);
+        _assert_close(-9.31434, position.declination, 0.01);
+        _assert_close(14.6415, position.equation_of_time, 0.05);
+        assert!(position.is_up());
+    }
+
+    #[test]
+    fn test_mid_interval() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let positions = epw.solar_position();
+        assert_eq!(epw.data.timestamp.len(), positions.len());
+
+        // The first record covers 00:00 to 01:00, so the sun is at 00:30
+        let location = &epw.header.location;
+        let expected = solar_position(
+            location.latitude,
+            location.longitude,
+            epw.data.timestamp[0] + TimeDelta::minutes(30),
+        );
+        assert_eq!(expected, positions[0]);
+        assert!(!positions[0].is_up());
+        assert!(positions[12].is_up());
+        assert!(positions[12].azimuth > 90. && positions[12].azimuth < 270.);
+    }
+}
@@ -0,0 +1,266 @@
+/*!
+This module contains the parser for `.stat` files, the climate statistics report that is
+distributed alongside EPW files.
+
+The report is free-form text meant for people rather than programs, so only the parts with a
+stable layout are read: the tab separated monthly tables, the Köppen classification, the ASHRAE
+climate zone and the monthly heating and cooling degree days.
+
+*/
+use crate::error::EPWParseError;
+use std::fs;
+use std::io::Read;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const CLIMATE_TYPE_KEY: &str = "Climate type";
+
+/// A row of a monthly table, values that are not numeric (e.g. `Day:Hour` rows) or missing from a
+/// partial row are `None`
+#[derive(Debug, PartialEq)]
+pub struct MonthlyRow {
+    pub label: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// A table of monthly values, e.g. "Monthly Statistics for Dry Bulb temperatures \[C\]"
+#[derive(Debug)]
+pub struct MonthlyTable {
+    pub title: String,
+    pub rows: Vec<MonthlyRow>,
+}
+
+impl MonthlyTable {
+    /// Find a row by its label
+    pub fn row(&self, label: &str) -> Option<&MonthlyRow> {
+        self.rows.iter().find(|row| row.label == label)
+    }
+}
+
+/// Monthly degree days as reported in the `.stat` file, one value per month
+#[derive(Debug, PartialEq)]
+pub struct StatDegreeDays {
+    pub heating_base_10c: Vec<f64>,
+    pub heating_base_18c: Vec<f64>,
+    pub cooling_base_10c: Vec<f64>,
+    pub cooling_base_18c: Vec<f64>,
+}
+
+/// STATFile is the representation of a parsed `.stat` file
+#[derive(Debug)]
+pub struct STATFile {
+    pub monthly_tables: Vec<MonthlyTable>,
+    /// Köppen classification code, e.g. `Cfa`
+    pub koppen_classification: Option<String>,
+    pub koppen_description: Option<String>,
+    /// ASHRAE climate zone, e.g. `2A`
+    pub ashrae_climate_zone: Option<String>,
+    pub ashrae_climate_zone_description: Option<String>,
+    pub degree_days: Option<StatDegreeDays>,
+}
+
+impl STATFile {
+    /// Construct a STATFile instance from a reader.
+    ///
+    /// ## Parameters
+    /// - `reader`: Reader that returns file contents.
+    ///
+    /// ## Returns
+    /// An initialized STATFile or an EPWParseError
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, EPWParseError> {
+        let mut content: Vec<u8> = Vec::new();
+        if let Err(e) = reader.read_to_end(&mut content) {
+            return Err(EPWParseError::Stat(format!("Unable to read file: {}", e)));
+        }
+        let content = String::from_utf8_lossy(&content);
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut monthly_tables: Vec<MonthlyTable> = Vec::new();
+        let mut koppen: Option<(String, Option<String>)> = None;
+        let mut ashrae: Option<(String, Option<String>)> = None;
+        let mut title = String::new();
+
+        for (idx, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("- ") && !line.starts_with("   ") {
+                title = trimmed.trim_start_matches("- ").to_string();
+                if let Some(code) = _parse_climate_type(trimmed) {
+                    let description = _parse_description(lines.get(idx + 1));
+                    if trimmed.contains("ppen") {
+                        koppen = Some((code, description));
+                    } else if trimmed.contains("ASHRAE") {
+                        ashrae = Some((code, description));
+                    }
+                }
+            } else if _is_month_header(line) {
+                monthly_tables.push(MonthlyTable {
+                    title: title.clone(),
+                    rows: Vec::new(),
+                });
+            } else if line.contains('\t') && !trimmed.is_empty() && !trimmed.starts_with('-') {
+                let table = match monthly_tables.last_mut() {
+                    Some(val) => val,
+                    None => continue,
+                };
+                let cells: Vec<&str> = line.split('\t').map(str::trim).collect();
+                // Notes and partial rows are common, missing months are padded with None
+                let mut values: Vec<Option<f64>> = cells[1..]
+                    .iter()
+                    .take(MONTHS.len())
+                    .map(|cell| cell.parse().ok())
+                    .collect();
+                values.resize(MONTHS.len(), None);
+                table.rows.push(MonthlyRow {
+                    label: cells[0].to_string(),
+                    values,
+                });
+            }
+        }
+
+        let degree_days = _find_degree_days(&monthly_tables);
+        let (koppen_classification, koppen_description) = match koppen {
+            Some((code, description)) => (Some(code), description),
+            None => (None, None),
+        };
+        let (ashrae_climate_zone, ashrae_climate_zone_description) = match ashrae {
+            Some((code, description)) => (Some(code), description),
+            None => (None, None),
+        };
+
+        Ok(Self {
+            monthly_tables,
+            koppen_classification,
+            koppen_description,
+            ashrae_climate_zone,
+            ashrae_climate_zone_description,
+            degree_days,
+        })
+    }
+
+    /// Create a STATFile instance from a file path
+    ///
+    /// ## Parameters
+    /// - `path`: Path to file on the filesystem
+    ///
+    /// ## Returns
+    /// An initialized STATFile or an EPWParseError
+    pub fn from_path(path: &str) -> Result<Self, EPWParseError> {
+        let f = match fs::File::open(path) {
+            Ok(val) => val,
+            Err(e) => return Err(EPWParseError::FileNotFound(e.to_string())),
+        };
+        Self::from_reader(f)
+    }
+
+    /// Find a monthly table by the start of its title, e.g. "Monthly Statistics for Dry Bulb"
+    pub fn monthly_table(&self, title: &str) -> Option<&MonthlyTable> {
+        self.monthly_tables
+            .iter()
+            .find(|table| table.title.starts_with(title))
+    }
+}
+
+fn _is_month_header(line: &str) -> bool {
+    let cells: Vec<&str> = line.split('\t').map(str::trim).collect();
+    MONTHS.iter().all(|month| cells.contains(month))
+}
+
+/// Read the quoted code out of a line like ` - Climate type "Cfa" (Köppen classification)*`
+fn _parse_climate_type(line: &str) -> Option<String> {
+    if !line.contains(CLIMATE_TYPE_KEY) {
+        return None;
+    }
+    let parts: Vec<&str> = line.split('"').collect();
+    match parts.len() >= 3 {
+        true => Some(parts[1].to_string()),
+        false => None,
+    }
+}
+
+fn _parse_description(line: Option<&&str>) -> Option<String> {
+    let line = line?.trim();
+    match line.starts_with("- ") {
+        true => Some(line.trim_start_matches("- ").to_string()),
+        false => None,
+    }
+}
+
+fn _find_degree_days(tables: &[MonthlyTable]) -> Option<StatDegreeDays> {
+    let find_row = |label: &str| -> Option<Vec<f64>> {
+        let row = tables.iter().find_map(|table| table.row(label))?;
+        row.values.iter().copied().collect()
+    };
+
+    Some(StatDegreeDays {
+        heating_base_10c: find_row("HDD base 10C")?,
+        heating_base_18c: find_row("HDD base 18C")?,
+        cooling_base_10c: find_row("CDD base 10C")?,
+        cooling_base_18c: find_row("CDD base 18C")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_STAT: &str = " Statistics for USA_FL_Tampa.Intl.AP.722110_TMY3
+ Location -- Tampa Intl Ap FL USA
+
+ - Monthly Statistics for Dry Bulb temperatures [C]
+                \tJan\tFeb\tMar\tApr\tMay\tJun\tJul\tAug\tSep\tOct\tNov\tDec
+ Maximum        \t 27.2\t 28.9\t 29.4\t 31.1\t 33.3\t 34.4\t 35.0\t 34.4\t 33.9\t 32.2\t 30.0\t 28.3
+ Day:Hour       \t 22:14\t 25:14\t 31:15\t 28:14\t 26:15\t 19:15\t 30:15\t  4:14\t  6:15\t  2:14\t  8:14\t  4:15
+
+ Minimum        \t  0.0\t  4.4\t  6.1\t 11.7\t 17.2\t 21.1\t 22.2\t 22.2\t 21.1\t 12.8\t  6.1\t  3.3
+ Daily Avg      \t 15.7\t 17.1\t 18.9
+ Note:\tsee the hourly table
+
+   - Maximum Dry Bulb temperature of  35.0°C on Jul 30
+
+ - Monthly Statistics for Heating/Cooling Degree Days/Hours
+                \tJan\tFeb\tMar\tApr\tMay\tJun\tJul\tAug\tSep\tOct\tNov\tDec
+ HDD base 10C   \t 11\t  1\t  0\t  0\t  0\t  0\t  0\t  0\t  0\t  0\t  0\t  3
+ HDD base 18C   \t151\t 80\t 42\t  3\t  0\t  0\t  0\t  0\t  0\t  3\t 41\t105
+ CDD base 10C   \t182\t212\t326\t387\t499\t541\t573\t572\t530\t431\t313\t220
+ CDD base 18C   \t 38\t 63\t120\t150\t251\t301\t325\t324\t290\t186\t114\t 74
+
+ - Climate type \"Cfa\" (Köppen classification)*
+   - Humid subtropical (warm summer, no dry season)
+
+ - Climate type \"2A\" (ASHRAE Standard 196-2006 Climate Zone)**
+   - Hot - Humid
+";
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = STATFile::from_reader(TEST_STAT.as_bytes()).unwrap();
+
+        assert_eq!(2, stat.monthly_tables.len());
+        let dry_bulb = stat
+            .monthly_table("Monthly Statistics for Dry Bulb")
+            .unwrap();
+        assert_eq!(Some(27.2), dry_bulb.row("Maximum").unwrap().values[0]);
+        assert_eq!(None, dry_bulb.row("Day:Hour").unwrap().values[0]);
+        assert_eq!(Some(3.3), dry_bulb.row("Minimum").unwrap().values[11]);
+        let partial = dry_bulb.row("Daily Avg").unwrap();
+        assert_eq!(12, partial.values.len());
+        assert_eq!((Some(18.9), None), (partial.values[2], partial.values[3]));
+        assert_eq!(vec![None; 12], dry_bulb.row("Note:").unwrap().values);
+
+        assert_eq!(Some("Cfa".to_string()), stat.koppen_classification);
+        assert_eq!(
+            Some("Humid subtropical (warm summer, no dry season)".to_string()),
+            stat.koppen_description
+        );
+        assert_eq!(Some("2A".to_string()), stat.ashrae_climate_zone);
+        assert_eq!(
+            Some("Hot - Humid".to_string()),
+            stat.ashrae_climate_zone_description
+        );
+
+        let degree_days = stat.degree_days.unwrap();
+        assert_eq!(151., degree_days.heating_base_18c[0]);
+        assert_eq!(573., degree_days.cooling_base_10c[6]);
+    }
+}
@@ -0,0 +1,285 @@
+/*!
+Monthly and annual climate summary statistics, the tables of the EnergyPlus `.stat` report
+computed from the weather data itself, so they stay correct for edited files.
+
+Records are grouped by the calendar month of their timestamp. Missing (NaN) values are skipped,
+and statistics over no values are NaN with a count of 0. Standard deviations are population
+standard deviations.
+
+*/
+use crate::{EPWFile, WeatherData};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate};
+
+/// Minimum, maximum, mean and standard deviation of a set of values
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Statistics {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub mean: f64,
+    pub standard_deviation: f64,
+    /// Number of values that were not missing
+    pub count: usize,
+}
+
+impl Statistics {
+    /// Compute the statistics of the values that are not NaN
+    pub fn from_values<'a, I: IntoIterator<Item = &'a f64>>(values: I) -> Self {
+        let values: Vec<f64> = values
+            .into_iter()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .collect();
+        if values.is_empty() {
+            return Self {
+                minimum: f64::NAN,
+                maximum: f64::NAN,
+                mean: f64::NAN,
+                standard_deviation: f64::NAN,
+                count: 0,
+            };
+        }
+
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+        Self {
+            minimum: values.iter().copied().fold(f64::INFINITY, f64::min),
+            maximum: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            standard_deviation: variance.sqrt(),
+            count,
+        }
+    }
+}
+
+/// Statistics of a column for each month and for the whole file
+#[derive(Debug)]
+pub struct ColumnSummary {
+    /// Field name of the column in [WeatherData]
+    pub name: &'static str,
+    /// One entry per month of [ClimateSummary::months]
+    pub monthly: Vec<Statistics>,
+    pub annual: Statistics,
+}
+
+/// Sums of a column for each month and for the whole file
+#[derive(Debug)]
+pub struct Totals {
+    /// One entry per month of [ClimateSummary::months]
+    pub monthly: Vec<f64>,
+    pub annual: f64,
+}
+
+/// Monthly and annual climate statistics
+#[derive(Debug)]
+pub struct ClimateSummary {
+    /// Months present in the data, 1 for January
+    pub months: Vec<u32>,
+    /// Statistics of every numeric column of [WeatherData]
+    pub columns: Vec<ColumnSummary>,
+    /// Statistics of the daily dry bulb temperature range (daily maximum minus daily minimum) in °C
+    pub daily_dry_bulb_range: ColumnSummary,
+    /// Global horizontal radiation in kWh/m²
+    pub global_horizontal_radiation: Totals,
+    /// Direct normal radiation in kWh/m²
+    pub direct_normal_radiation: Totals,
+    /// Diffuse horizontal radiation in kWh/m²
+    pub diffuse_horizontal_radiation: Totals,
+    /// Liquid precipitation depth in mm
+    pub liquid_precipitation_depth: Totals,
+}
+
+impl ClimateSummary {
+    /// Find the statistics of a column by its field name, e.g. `dry_bulb_temperature`
+    pub fn column(&self, name: &str) -> Option<&ColumnSummary> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+impl EPWFile {
+    /// Compute monthly and annual statistics of the weather data
+    ///
+    /// ## Returns
+    /// The climate summary
+    pub fn summary(&self) -> ClimateSummary {
+        let data = &self.data;
+        let record_months: Vec<u32> = data.timestamp.iter().map(|t| t.month()).collect();
+        let mut months: Vec<u32> = record_months.clone();
+        months.sort();
+        months.dedup();
+
+        let columns = data
+            .numeric_columns()
+            .into_iter()
+            .map(|(name, values)| _summarize(name, values, &record_months, &months))
+            .collect();
+
+        let (day_months, day_ranges) = daily_dry_bulb_ranges(data);
+        let daily_dry_bulb_range =
+            _summarize("daily_dry_bulb_range", &day_ranges, &day_months, &months);
+
+        // Radiation values are mean irradiances over each interval, so the energy of a record in
+        // kWh/m² is its value times the interval length in hours over 1000
+        let radiation_scale =
+            1. / (1000. * self.header.data_periods.records_per_hour.max(1) as f64);
+        ClimateSummary {
+            global_horizontal_radiation: _totals(
+                &data.global_horizontal_radiation,
+                &record_months,
+                &months,
+                radiation_scale,
+            ),
+            direct_normal_radiation: _totals(
+                &data.direct_normal_radiation,
+                &record_months,
+                &months,
+                radiation_scale,
+            ),
+            diffuse_horizontal_radiation: _totals(
+                &data.diffuse_horizontal_radiation,
+                &record_months,
+                &months,
+                radiation_scale,
+            ),
+            liquid_precipitation_depth: _totals(
+                &data.liquid_precipitation_depth,
+                &record_months,
+                &months,
+                1.,
+            ),
+            months,
+            columns,
+            daily_dry_bulb_range,
+        }
+    }
+}
+
+fn _summarize(
+    name: &'static str,
+    values: &[f64],
+    record_months: &[u32],
+    months: &[u32],
+) -> ColumnSummary {
+    let monthly = months
+        .iter()
+        .map(|month| {
+            Statistics::from_values(
+                values
+                    .iter()
+                    .zip(record_months)
+                    .filter(|(_, m)| *m == month)
+                    .map(|(v, _)| v),
+            )
+        })
+        .collect();
+    ColumnSummary {
+        name,
+        monthly,
+        annual: Statistics::from_values(values),
+    }
+}
+
+fn _totals(values: &[f64], record_months: &[u32], months: &[u32], scale: f64) -> Totals {
+    let monthly: Vec<f64> = months
+        .iter()
+        .map(|month| {
+            values
+                .iter()
+                .zip(record_months)
+                .filter(|(v, m)| *m == month && !v.is_nan())
+                .map(|(v, _)| v * scale)
+                .sum()
+        })
+        .collect();
+    Totals {
+        annual: monthly.iter().sum(),
+        monthly,
+    }
+}
+
+/// Mean of the values of each calendar month, January first, NaN for months without values
+pub(crate) fn monthly_means(timestamps: &[DateTime<FixedOffset>], values: &[f64]) -> Vec<f64> {
+    (1..=12)
+        .map(|month| {
+            Statistics::from_values(
+                values
+                    .iter()
+                    .zip(timestamps)
+                    .filter(|(_, t)| t.month() == month)
+                    .map(|(v, _)| v),
+            )
+            .mean
+        })
+        .collect()
+}
+
+/// Sum of the values of each calendar month, January first, skipping missing values
+pub(crate) fn monthly_totals(timestamps: &[DateTime<FixedOffset>], values: &[f64]) -> Vec<f64> {
+    (1..=12)
+        .map(|month| {
+            values
+                .iter()
+                .zip(timestamps)
+                .filter(|(v, t)| t.month() == month && !v.is_nan())
+                .map(|(v, _)| v)
+                .sum()
+        })
+        .collect()
+}
+
+/// The month and dry bulb temperature range of each day
+pub(crate) fn daily_dry_bulb_ranges(data: &WeatherData) -> (Vec<u32>, Vec<f64>) {
+    let mut dates: Vec<NaiveDate> = Vec::new();
+    let mut ranges: Vec<(f64, f64)> = Vec::new();
+    for (timestamp, temperature) in data.timestamp.iter().zip(&data.dry_bulb_temperature) {
+        let date = timestamp.date_naive();
+        if dates.last() != Some(&date) {
+            dates.push(date);
+            ranges.push((f64::INFINITY, f64::NEG_INFINITY));
+        }
+        if let Some(range) = ranges.last_mut() {
+            range.0 = range.0.min(*temperature);
+            range.1 = range.1.max(*temperature);
+        }
+    }
+
+    let months = dates.iter().map(|date| date.month()).collect();
+    let ranges = ranges
+        .into_iter()
+        .map(|(low, high)| match high >= low {
+            true => high - low,
+            false => f64::NAN,
+        })
+        .collect();
+    (months, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let summary = epw.summary();
+        assert_eq!((1..=12).collect::<Vec<u32>>(), summary.months);
+        assert_eq!(epw.data.numeric_columns().len(), summary.columns.len());
+
+        let dry_bulb = summary.column("dry_bulb_temperature").unwrap();
+        let january = &epw.data.dry_bulb_temperature[..744];
+        let expected = january.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(expected, dry_bulb.monthly[0].maximum);
+        assert_eq!(744, dry_bulb.monthly[0].count);
+        assert_eq!(8760, dry_bulb.annual.count);
+        assert!(dry_bulb.annual.minimum <= dry_bulb.monthly[0].minimum);
+
+        let range = &summary.daily_dry_bulb_range;
+        assert_eq!(31, range.monthly[0].count);
+        assert!(range.annual.mean > 0. && range.annual.maximum < dry_bulb.annual.maximum);
+
+        let ghi = &summary.global_horizontal_radiation;
+        let expected: f64 = epw.data.global_horizontal_radiation.iter().sum::<f64>() / 1000.;
+        assert!((expected - ghi.annual).abs() < 1e-6);
+        assert!(ghi.monthly[6] > ghi.monthly[11]);
+    }
+}
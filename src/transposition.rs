@@ -0,0 +1,281 @@
+/*!
+Irradiance on tilted and oriented surfaces (plane-of-array irradiance).
+
+The irradiance on a surface is split into three components:
+- beam: the direct normal irradiance projected onto the surface
+- sky diffuse: the part of the diffuse horizontal irradiance seen by the surface, from one of the
+  [SkyDiffuseModel]s
+- ground reflected: the global horizontal irradiance reflected by the ground, assuming an
+  isotropic ground of uniform reflectance
+
+Surfaces are described by their tilt from horizontal (0° faces up, 90° is a wall) and the azimuth
+of their outward normal, clockwise from north (180° faces south), the same convention as
+[crate::solar::SolarPosition::azimuth].
+
+The results have the same units as the radiation fields of [WeatherData], Wh/m² over each
+interval. Records with missing radiation values give NaN.
+
+*/
+use crate::solar::{relative_air_mass, SolarPosition};
+use crate::{EPWFile, WeatherData};
+
+/// Ground reflectance used when none is given and the record has no albedo value
+pub const DEFAULT_GROUND_REFLECTANCE: f64 = 0.2;
+
+/// Upper bounds of the Perez sky clearness bins
+const PEREZ_CLEARNESS_BINS: [f64; 7] = [1.065, 1.23, 1.5, 1.95, 2.8, 4.5, 6.2];
+
+/// Perez et al. (1990) brightness coefficients F11, F12, F13, F21, F22, F23 for each sky
+/// clearness bin, fitted to the composite of all sites
+#[rustfmt::skip]
+const PEREZ_COEFFICIENTS: [[f64; 6]; 8] = [
+    [-0.0083117, 0.5877285, -0.0620636, -0.0596012, 0.0721249, -0.0220216],
+    [0.1299457, 0.6825954, -0.1513752, -0.0189325, 0.0659650, -0.0288748],
+    [0.3296958, 0.4868735, -0.2210958, 0.0554140, -0.0639588, -0.0260542],
+    [0.5682053, 0.1874525, -0.2951290, 0.1088631, -0.1519229, -0.0139754],
+    [0.8730280, -0.3920403, -0.3616149, 0.2255647, -0.4620442, 0.0012448],
+    [1.1326077, -1.2367284, -0.4118494, 0.2877813, -0.8230357, 0.0558225],
+    [1.0601591, -1.5999137, -0.3589221, 0.2642124, -1.1272340, 0.1310694],
+    [0.6777470, -0.3272588, -0.2504286, 0.1561313, -1.3765031, 0.2506212],
+];
+
+/// Model used to distribute the diffuse irradiance over the sky dome
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SkyDiffuseModel {
+    /// Uniform sky radiance (Liu and Jordan, 1963)
+    Isotropic,
+    /// Isotropic sky with a circumsolar component weighted by the anisotropy index
+    /// (Hay and Davies, 1980)
+    HayDavies,
+    /// Isotropic sky with circumsolar and horizon brightening components (Perez et al., 1990)
+    Perez,
+}
+
+/// Irradiance components on a surface for each record
+#[derive(Debug)]
+pub struct PlaneOfArrayIrradiance {
+    pub beam: Vec<f64>,
+    pub sky_diffuse: Vec<f64>,
+    pub ground_reflected: Vec<f64>,
+}
+
+impl PlaneOfArrayIrradiance {
+    /// Total irradiance on the surface for each record
+    pub fn total(&self) -> Vec<f64> {
+        self.beam
+            .iter()
+            .zip(&self.sky_diffuse)
+            .zip(&self.ground_reflected)
+            .map(|((beam, sky), ground)| beam + sky + ground)
+            .collect()
+    }
+}
+
+impl WeatherData {
+    /// Compute the irradiance on a tilted and oriented surface
+    ///
+    /// ## Parameters
+    /// - `solar_position`: Position of the sun for each record, see [WeatherData::solar_position]
+    /// - `tilt`: Tilt of the surface from horizontal in degrees
+    /// - `azimuth`: Azimuth of the surface normal in degrees clockwise from north
+    /// - `ground_reflectance`: Reflectance of the ground, defaults to the albedo of each record or
+    ///   [DEFAULT_GROUND_REFLECTANCE] when the record has none
+    /// - `model`: Sky diffuse model
+    ///
+    /// ## Returns
+    /// The beam, sky diffuse and ground reflected irradiance for each record
+    pub fn plane_of_array_irradiance(
+        &self,
+        solar_position: &[SolarPosition],
+        tilt: f64,
+        azimuth: f64,
+        ground_reflectance: Option<f64>,
+        model: SkyDiffuseModel,
+    ) -> PlaneOfArrayIrradiance {
+        let mut irradiance = PlaneOfArrayIrradiance {
+            beam: Vec::with_capacity(solar_position.len()),
+            sky_diffuse: Vec::with_capacity(solar_position.len()),
+            ground_reflected: Vec::with_capacity(solar_position.len()),
+        };
+
+        let beta = tilt.to_radians();
+        for (idx, sun) in solar_position.iter().enumerate() {
+            let dni = self.direct_normal_radiation[idx];
+            let dhi = self.diffuse_horizontal_radiation[idx];
+            let ghi = self.global_horizontal_radiation[idx];
+            let dni_extra = self.extraterrestrial_direct_normal_radiation[idx];
+
+            let cos_zenith = sun.zenith.to_radians().cos();
+            let cos_incidence = cos_zenith * beta.cos()
+                + sun.zenith.to_radians().sin()
+                    * beta.sin()
+                    * (sun.azimuth - azimuth).to_radians().cos();
+
+            let beam = match sun.is_up() {
+                true => dni * cos_incidence.max(0.),
+                false => 0.,
+            };
+            let sky_diffuse = match (sun.is_up(), model) {
+                (false, _) | (_, SkyDiffuseModel::Isotropic) => _isotropic(dhi, beta),
+                (true, SkyDiffuseModel::HayDavies) => {
+                    _hay_davies(dni, dhi, dni_extra, cos_zenith, cos_incidence, beta)
+                }
+                (true, SkyDiffuseModel::Perez) => {
+                    _perez(dni, dhi, dni_extra, sun.zenith, cos_incidence, beta)
+                }
+            };
+
+            let albedo = match ground_reflectance {
+                Some(val) => val,
+                None => match self.albedo[idx].is_nan() {
+                    true => DEFAULT_GROUND_REFLECTANCE,
+                    false => self.albedo[idx],
+                },
+            };
+
+            irradiance.beam.push(beam);
+            irradiance.sky_diffuse.push(sky_diffuse);
+            irradiance
+                .ground_reflected
+                .push(ghi * albedo * (1. - beta.cos()) / 2.);
+        }
+        irradiance
+    }
+}
+
+impl EPWFile {
+    /// Compute the irradiance on a tilted and oriented surface
+    ///
+    /// ## Parameters
+    /// - `tilt`: Tilt of the surface from horizontal in degrees
+    /// - `azimuth`: Azimuth of the surface normal in degrees clockwise from north
+    /// - `ground_reflectance`: Reflectance of the ground, defaults to the albedo of each record or
+    ///   [DEFAULT_GROUND_REFLECTANCE] when the record has none
+    /// - `model`: Sky diffuse model
+    ///
+    /// ## Returns
+    /// The beam, sky diffuse and ground reflected irradiance for each record
+    pub fn plane_of_array_irradiance(
+        &self,
+        tilt: f64,
+        azimuth: f64,
+        ground_reflectance: Option<f64>,
+        model: SkyDiffuseModel,
+    ) -> PlaneOfArrayIrradiance {
+        self.data.plane_of_array_irradiance(
+            &self.solar_position(),
+            tilt,
+            azimuth,
+            ground_reflectance,
+            model,
+        )
+    }
+}
+
+fn _isotropic(dhi: f64, beta: f64) -> f64 {
+    dhi * (1. + beta.cos()) / 2.
+}
+
+fn _hay_davies(
+    dni: f64,
+    dhi: f64,
+    dni_extra: f64,
+    cos_zenith: f64,
+    cos_incidence: f64,
+    beta: f64,
+) -> f64 {
+    let anisotropy = match dni_extra > 0. {
+        true => (dni / dni_extra).clamp(0., 1.),
+        false => 0.,
+    };
+    let beam_ratio = cos_incidence.max(0.) / cos_zenith.max(89_f64.to_radians().cos());
+    dhi * ((1. - anisotropy) * (1. + beta.cos()) / 2. + anisotropy * beam_ratio)
+}
+
+fn _perez(dni: f64, dhi: f64, dni_extra: f64, zenith: f64, cos_incidence: f64, beta: f64) -> f64 {
+    if dni.is_nan() || dhi.is_nan() || dni_extra.is_nan() {
+        return f64::NAN;
+    }
+    if dhi <= 0. || dni_extra <= 0. {
+        return _isotropic(dhi, beta);
+    }
+
+    let z = zenith.to_radians();
+    let brightness = perez_sky_brightness(dhi, dni_extra, zenith);
+    let bin = perez_clearness_bin(perez_sky_clearness(dni, dhi, zenith));
+    let f = PEREZ_COEFFICIENTS[bin];
+    let f1 = (f[0] + f[1] * brightness + f[2] * z).max(0.);
+    let f2 = f[3] + f[4] * brightness + f[5] * z;
+
+    let a = cos_incidence.max(0.);
+    let b = z.cos().max(85_f64.to_radians().cos());
+    dhi * ((1. - f1) * (1. + beta.cos()) / 2. + f1 * a / b + f2 * beta.sin())
+}
+
+/// Perez sky clearness from the direct normal and diffuse horizontal irradiance and the zenith
+/// angle in degrees
+pub(crate) fn perez_sky_clearness(dni: f64, dhi: f64, zenith: f64) -> f64 {
+    let kappa_z3 = 1.041 * zenith.to_radians().powi(3);
+    ((dhi + dni) / dhi + kappa_z3) / (1. + kappa_z3)
+}
+
+/// Perez sky brightness from the diffuse horizontal and extraterrestrial normal irradiance and the
+/// zenith angle in degrees
+pub(crate) fn perez_sky_brightness(dhi: f64, dni_extra: f64, zenith: f64) -> f64 {
+    dhi * relative_air_mass(zenith) / dni_extra
+}
+
+/// Index of the Perez sky clearness bin, from 0 (overcast) to 7 (clear)
+pub(crate) fn perez_clearness_bin(clearness: f64) -> usize {
+    PEREZ_CLEARNESS_BINS
+        .iter()
+        .position(|upper| clearness < *upper)
+        .unwrap_or(PEREZ_CLEARNESS_BINS.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plane_of_array_irradiance() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let data = &epw.data;
+        let sun = epw.solar_position();
+        let noon = 12;
+
+        // A horizontal surface sees the horizontal components, whatever the model
+        for model in [
+            SkyDiffuseModel::Isotropic,
+            SkyDiffuseModel::HayDavies,
+            SkyDiffuseModel::Perez,
+        ] {
+            let horizontal = epw.plane_of_array_irradiance(0., 180., None, model);
+            let expected_beam =
+                data.direct_normal_radiation[noon] * sun[noon].zenith.to_radians().cos();
+            assert!((expected_beam - horizontal.beam[noon]).abs() < 1e-6);
+            assert!(
+                (data.diffuse_horizontal_radiation[noon] - horizontal.sky_diffuse[noon]).abs()
+                    < 1e-6
+            );
+            assert_eq!(0., horizontal.ground_reflected[noon]);
+            assert_eq!(0., horizontal.beam[0]);
+        }
+
+        // In January a north facing wall in Florida gets no beam irradiance at noon, and half the
+        // isotropic sky and ground
+        let north = epw.plane_of_array_irradiance(90., 0., Some(0.3), SkyDiffuseModel::Isotropic);
+        assert_eq!(0., north.beam[noon]);
+        assert!(
+            (data.diffuse_horizontal_radiation[noon] / 2. - north.sky_diffuse[noon]).abs() < 1e-6
+        );
+        assert!(
+            (data.global_horizontal_radiation[noon] * 0.15 - north.ground_reflected[noon]).abs()
+                < 1e-6
+        );
+
+        let south = epw.plane_of_array_irradiance(90., 180., Some(0.3), SkyDiffuseModel::Perez);
+        assert!(south.beam[noon] > 0.);
+        assert!(south.total()[noon] > north.total()[noon]);
+    }
+}
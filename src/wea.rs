@@ -0,0 +1,98 @@
+/*!
+Export of weather data to the Radiance/Daysim `.wea` format.
+
+A `.wea` file is a short header describing the site followed by one line per record containing the
+month, day, decimal hour, direct normal irradiance and diffuse horizontal irradiance. Radiance uses
+the opposite sign convention to EnergyPlus for the longitude and time zone, so both are positive
+to the west of Greenwich, and the time zone is given as the longitude of the standard meridian.
+
+*/
+use crate::EPWFile;
+use chrono::{Datelike, TimeDelta, Timelike};
+use std::io::Write;
+
+impl EPWFile {
+    /// Write the weather data to a writer in the Radiance/Daysim `.wea` format.
+    ///
+    /// Each record is written at the middle of the interval it covers, e.g. `0.500` for the first
+    /// hour of an hourly file and `0.125` for the first record of a 15 minute file. Irradiance is
+    /// written in W/m² (`weather_data_file_units 1`), missing values are written as `0`.
+    ///
+    /// ## Parameters
+    /// - `writer`: Destination for the `.wea` content
+    ///
+    /// ## Returns
+    /// Nothing, or the IO error raised by the writer
+    pub fn to_wea<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let location = &self.header.location;
+        let time_zone = location.time_zone.local_minus_utc() as f64 / 3600.;
+        let place = format!("{}_{}", location.city, location.country).replace(' ', "_");
+
+        writeln!(writer, "place {}", place)?;
+        writeln!(writer, "latitude {:.2}", location.latitude)?;
+        writeln!(writer, "longitude {:.2}", -location.longitude)?;
+        writeln!(writer, "time_zone {:.0}", -time_zone * 15.)?;
+        writeln!(writer, "site_elevation {:.1}", location.elevation)?;
+        writeln!(writer, "weather_data_file_units 1")?;
+
+        let records_per_hour = self.header.data_periods.records_per_hour.max(1) as i64;
+        let half_interval = TimeDelta::seconds(1800 / records_per_hour);
+        let data = &self.data;
+        for idx in 0..data.timestamp.len() {
+            let midpoint = data.timestamp[idx] + half_interval;
+            let hour = midpoint.hour() as f64
+                + midpoint.minute() as f64 / 60.
+                + midpoint.second() as f64 / 3600.;
+            writeln!(
+                writer,
+                "{} {} {:.3} {:.0} {:.0}",
+                midpoint.month(),
+                midpoint.day(),
+                hour,
+                _irradiance(data.direct_normal_radiation[idx]),
+                _irradiance(data.diffuse_horizontal_radiation[idx]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn _irradiance(value: f64) -> f64 {
+    match value.is_nan() {
+        true => 0.,
+        false => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EPWFile;
+    use std::fs;
+
+    const TEST_FILE: &str = "./data/USA_FL_Tampa_TMY2.epw";
+
+    #[test]
+    fn test_to_wea_sub_hourly() {
+        let content = fs::read_to_string(TEST_FILE).unwrap();
+        let mut lines: Vec<String> = content.lines().take(9).map(String::from).collect();
+        lines[7] = "DATA PERIODS,1,4,Data,Sunday, 1/ 1,12/31".to_string();
+        let row = lines.pop().unwrap();
+        for minute in ["15", "30", "45", "60"] {
+            lines.push(row.replacen(",60,", &format!(",{},", minute), 1));
+        }
+
+        let epw = EPWFile::from_reader(lines.join("\n").as_bytes()).unwrap();
+        let mut wea: Vec<u8> = Vec::new();
+        epw.to_wea(&mut wea).unwrap();
+        let wea = String::from_utf8(wea).unwrap();
+        let wea: Vec<&str> = wea.lines().collect();
+
+        assert_eq!("place TAMPA_USA", wea[0]);
+        assert_eq!("longitude 82.53", wea[2]);
+        assert_eq!("time_zone 75", wea[3]);
+        assert_eq!("1 1 0.125 0 0", wea[6]);
+        assert_eq!("1 1 0.375 0 0", wea[7]);
+        assert_eq!("1 1 0.625 0 0", wea[8]);
+        assert_eq!("1 1 0.875 0 0", wea[9]);
+    }
+}
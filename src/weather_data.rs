@@ -7,7 +7,7 @@ use chrono::{DateTime, FixedOffset};
 /// The present weather struct based on TMY2 conventions.  Note that the most important fields are
 /// those representing liquid precipitation - where the surfaces of the  building would be wet.
 /// EnergyPlus uses “Snow Depth” to determine if snow is on the ground
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PresentWeather {
     /// Occurrence of Thunderstorm, Tornado, or Squall
     /// ### Definition:
@@ -158,7 +158,7 @@ pub struct PresentWeather {
 /// This library uses the convention of inserting NaN when a value is not available, rather than
 /// using the in-band magic numbers (e.g. 999) to signify missing data.
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WeatherData {
     /// Timestamps for the weather data samples
     pub timestamp: Vec<DateTime<FixedOffset>>,
@@ -254,6 +254,134 @@ pub struct WeatherData {
     pub liquid_precipitation_quantity: Vec<f64>,
 }
 
+impl WeatherData {
+    /// The numeric columns with their field names, in file order
+    pub fn numeric_columns(&self) -> Vec<(&'static str, &[f64])> {
+        vec![
+            ("dry_bulb_temperature", &self.dry_bulb_temperature),
+            ("dew_point_temperature", &self.dew_point_temperature),
+            ("relative_humidity", &self.relative_humidity),
+            ("atmospheric_pressure", &self.atmospheric_pressure),
+            (
+                "extraterrestrial_horizontal_radiation",
+                &self.extraterrestrial_horizontal_radiation,
+            ),
+            (
+                "extraterrestrial_direct_normal_radiation",
+                &self.extraterrestrial_direct_normal_radiation,
+            ),
+            (
+                "horizontal_infrared_radiation_intensity",
+                &self.horizontal_infrared_radiation_intensity,
+            ),
+            (
+                "global_horizontal_radiation",
+                &self.global_horizontal_radiation,
+            ),
+            ("direct_normal_radiation", &self.direct_normal_radiation),
+            (
+                "diffuse_horizontal_radiation",
+                &self.diffuse_horizontal_radiation,
+            ),
+            (
+                "global_horizontal_illuminance",
+                &self.global_horizontal_illuminance,
+            ),
+            ("direct_normal_illuminance", &self.direct_normal_illuminance),
+            (
+                "diffuse_horizontal_illuminance",
+                &self.diffuse_horizontal_illuminance,
+            ),
+            ("zenith_luminance", &self.zenith_luminance),
+            ("wind_direction", &self.wind_direction),
+            ("wind_speed", &self.wind_speed),
+            ("total_sky_cover", &self.total_sky_cover),
+            ("opaque_sky_cover", &self.opaque_sky_cover),
+            ("visibility", &self.visibility),
+            ("ceiling_height", &self.ceiling_height),
+            ("precipitable_water", &self.precipitable_water),
+            ("aerosol_optical_depth", &self.aerosol_optical_depth),
+            ("snow_depth", &self.snow_depth),
+            ("days_since_last_snowfall", &self.days_since_last_snowfall),
+            ("albedo", &self.albedo),
+            (
+                "liquid_precipitation_depth",
+                &self.liquid_precipitation_depth,
+            ),
+            (
+                "liquid_precipitation_quantity",
+                &self.liquid_precipitation_quantity,
+            ),
+        ]
+    }
+
+    /// Mutable access to the numeric columns with their field names, in file order
+    pub fn numeric_columns_mut(&mut self) -> Vec<(&'static str, &mut Vec<f64>)> {
+        vec![
+            ("dry_bulb_temperature", &mut self.dry_bulb_temperature),
+            ("dew_point_temperature", &mut self.dew_point_temperature),
+            ("relative_humidity", &mut self.relative_humidity),
+            ("atmospheric_pressure", &mut self.atmospheric_pressure),
+            (
+                "extraterrestrial_horizontal_radiation",
+                &mut self.extraterrestrial_horizontal_radiation,
+            ),
+            (
+                "extraterrestrial_direct_normal_radiation",
+                &mut self.extraterrestrial_direct_normal_radiation,
+            ),
+            (
+                "horizontal_infrared_radiation_intensity",
+                &mut self.horizontal_infrared_radiation_intensity,
+            ),
+            (
+                "global_horizontal_radiation",
+                &mut self.global_horizontal_radiation,
+            ),
+            ("direct_normal_radiation", &mut self.direct_normal_radiation),
+            (
+                "diffuse_horizontal_radiation",
+                &mut self.diffuse_horizontal_radiation,
+            ),
+            (
+                "global_horizontal_illuminance",
+                &mut self.global_horizontal_illuminance,
+            ),
+            (
+                "direct_normal_illuminance",
+                &mut self.direct_normal_illuminance,
+            ),
+            (
+                "diffuse_horizontal_illuminance",
+                &mut self.diffuse_horizontal_illuminance,
+            ),
+            ("zenith_luminance", &mut self.zenith_luminance),
+            ("wind_direction", &mut self.wind_direction),
+            ("wind_speed", &mut self.wind_speed),
+            ("total_sky_cover", &mut self.total_sky_cover),
+            ("opaque_sky_cover", &mut self.opaque_sky_cover),
+            ("visibility", &mut self.visibility),
+            ("ceiling_height", &mut self.ceiling_height),
+            ("precipitable_water", &mut self.precipitable_water),
+            ("aerosol_optical_depth", &mut self.aerosol_optical_depth),
+            ("snow_depth", &mut self.snow_depth),
+            (
+                "days_since_last_snowfall",
+                &mut self.days_since_last_snowfall,
+            ),
+            ("albedo", &mut self.albedo),
+            (
+                "liquid_precipitation_depth",
+                &mut self.liquid_precipitation_depth,
+            ),
+            (
+                "liquid_precipitation_quantity",
+                &mut self.liquid_precipitation_quantity,
+            ),
+        ]
+    }
+}
+
 #[cfg(feature = "polars")]
 pub mod polars {
     use super::WeatherData;
@@ -340,5 +468,27 @@ pub mod polars {
                 Err(e) => Err(e),
             }
         }
+
+        /// Build a DataFrame with the weather data followed by the derived psychrometric
+        /// columns: `partial_vapor_pressure`, `humidity_ratio`, `wet_bulb_temperature`,
+        /// `enthalpy` and `specific_volume`
+        pub fn to_dataframe_with_psychrometrics(&self) -> Result<DataFrame, PolarsError> {
+            let mut df = self.to_dataframe()?;
+            df.with_column(Series::new(
+                "partial_vapor_pressure".into(),
+                self.partial_vapor_pressure(),
+            ))?;
+            df.with_column(Series::new("humidity_ratio".into(), self.humidity_ratio()))?;
+            df.with_column(Series::new(
+                "wet_bulb_temperature".into(),
+                self.wet_bulb_temperature(),
+            ))?;
+            df.with_column(Series::new("enthalpy".into(), self.enthalpy()))?;
+            df.with_column(Series::new(
+                "specific_volume".into(),
+                self.specific_volume(),
+            ))?;
+            Ok(df)
+        }
     }
 }
@@ -0,0 +1,296 @@
+/*!
+Wind roses and wind speed statistics.
+
+A [WindRose] gives the fraction of the records in each direction sector and speed class. Records
+with a wind speed below [CALM_WIND_SPEED] are calms, counted separately as they have no direction.
+Records with a missing speed, or a missing direction when they are not calm, are skipped.
+
+Sectors are centered on their direction, so the first sector is centered on north and covers the
+directions within half a sector width on either side of it. Speed classes are given by their upper
+bounds: the first class covers the speeds from [CALM_WIND_SPEED] to the first bound, and the last
+class the speeds above the last bound.
+
+The Weibull distribution is fitted by maximum likelihood to the speeds of the records that are not
+calm.
+
+*/
+use crate::WeatherData;
+use chrono::{DateTime, Datelike, FixedOffset};
+
+/// Wind speed in m/s below which a record is calm
+pub const CALM_WIND_SPEED: f64 = 0.5;
+
+/// Upper bounds of the default wind speed classes in m/s
+pub const DEFAULT_SPEED_CLASSES: [f64; 5] = [2., 4., 6., 8., 10.];
+
+/// Number of direction sectors of a wind rose
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DirectionSectors {
+    /// 45° sectors, N, NE, E...
+    Eight,
+    /// 22.5° sectors, N, NNE, NE...
+    Sixteen,
+    /// 10° sectors
+    ThirtySix,
+}
+
+impl DirectionSectors {
+    /// Number of sectors
+    pub fn count(&self) -> usize {
+        match self {
+            DirectionSectors::Eight => 8,
+            DirectionSectors::Sixteen => 16,
+            DirectionSectors::ThirtySix => 36,
+        }
+    }
+
+    /// Width of a sector in degrees
+    pub fn width(&self) -> f64 {
+        360. / self.count() as f64
+    }
+}
+
+/// Parameters of a Weibull distribution of wind speeds
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WeibullParameters {
+    /// Shape parameter k
+    pub shape: f64,
+    /// Scale parameter c in m/s
+    pub scale: f64,
+}
+
+/// Frequencies of the wind directions and speeds
+#[derive(Debug)]
+pub struct WindRose {
+    pub sectors: DirectionSectors,
+    /// Upper bounds of the speed classes in m/s, the last class is open ended
+    pub speed_classes: Vec<f64>,
+    /// Fraction of the records in each sector and speed class, indexed by sector then class
+    pub frequencies: Vec<Vec<f64>>,
+    /// Fraction of the records that are calm
+    pub calm_fraction: f64,
+    /// Number of records counted in the wind rose
+    pub record_count: usize,
+    /// Center of the sector with the most records in degrees, NaN without records
+    pub prevailing_direction: f64,
+    /// Mean wind speed in m/s, including calms
+    pub mean_speed: f64,
+    /// Weibull distribution of the speeds that are not calm, `None` with fewer than two of them
+    pub weibull: Option<WeibullParameters>,
+}
+
+impl WindRose {
+    /// Center of a sector in degrees
+    pub fn sector_direction(&self, sector: usize) -> f64 {
+        sector as f64 * self.sectors.width()
+    }
+
+    /// Fraction of the records in each sector, all speed classes together
+    pub fn sector_frequencies(&self) -> Vec<f64> {
+        self.frequencies
+            .iter()
+            .map(|classes| classes.iter().sum())
+            .collect()
+    }
+}
+
+impl WeatherData {
+    /// Compute the wind rose of all the records
+    ///
+    /// ## Parameters
+    /// - `sectors`: Number of direction sectors
+    /// - `speed_classes`: Increasing upper bounds of the speed classes in m/s, e.g.
+    ///   [DEFAULT_SPEED_CLASSES]
+    ///
+    /// ## Returns
+    /// The wind rose
+    pub fn wind_rose(&self, sectors: DirectionSectors, speed_classes: &[f64]) -> WindRose {
+        self.wind_rose_filtered(sectors, speed_classes, |_| true)
+    }
+
+    /// Compute the wind rose of the records selected by their timestamp, e.g. the daytime records
+    /// with `|t| (8..18).contains(&t.hour())` or the summer ones with
+    /// `|t| (6..=8).contains(&t.month())`
+    ///
+    /// ## Parameters
+    /// - `sectors`: Number of direction sectors
+    /// - `speed_classes`: Increasing upper bounds of the speed classes in m/s, e.g.
+    ///   [DEFAULT_SPEED_CLASSES]
+    /// - `filter`: Returns true for the timestamps of the records to include
+    ///
+    /// ## Returns
+    /// The wind rose
+    pub fn wind_rose_filtered<F: Fn(&DateTime<FixedOffset>) -> bool>(
+        &self,
+        sectors: DirectionSectors,
+        speed_classes: &[f64],
+        filter: F,
+    ) -> WindRose {
+        let width = sectors.width();
+        let mut counts = vec![vec![0usize; speed_classes.len() + 1]; sectors.count()];
+        let mut calms = 0usize;
+        let mut speeds: Vec<f64> = Vec::new();
+        let mut speed_sum = 0.;
+
+        for (idx, timestamp) in self.timestamp.iter().enumerate() {
+            let speed = self.wind_speed[idx];
+            let direction = self.wind_direction[idx];
+            if !filter(timestamp) || speed.is_nan() {
+                continue;
+            }
+            if speed < CALM_WIND_SPEED {
+                calms += 1;
+                speed_sum += speed;
+                continue;
+            }
+            if direction.is_nan() {
+                continue;
+            }
+
+            let sector =
+                ((direction.rem_euclid(360.) + width / 2.) / width) as usize % counts.len();
+            let class = speed_classes
+                .iter()
+                .position(|bound| speed < *bound)
+                .unwrap_or(speed_classes.len());
+            counts[sector][class] += 1;
+            speeds.push(speed);
+            speed_sum += speed;
+        }
+
+        let record_count = calms + speeds.len();
+        let total = record_count.max(1) as f64;
+        let frequencies: Vec<Vec<f64>> = counts
+            .iter()
+            .map(|classes| classes.iter().map(|count| *count as f64 / total).collect())
+            .collect();
+        let prevailing_direction = match speeds.is_empty() {
+            true => f64::NAN,
+            false => {
+                let sector_counts: Vec<usize> = counts.iter().map(|c| c.iter().sum()).collect();
+                let sector = (0..sector_counts.len())
+                    .max_by_key(|sector| sector_counts[*sector])
+                    .unwrap_or(0);
+                sector as f64 * width
+            }
+        };
+
+        WindRose {
+            sectors,
+            speed_classes: speed_classes.to_vec(),
+            frequencies,
+            calm_fraction: calms as f64 / total,
+            record_count,
+            prevailing_direction,
+            mean_speed: match record_count {
+                0 => f64::NAN,
+                _ => speed_sum / total,
+            },
+            weibull: _fit_weibull(&speeds),
+        }
+    }
+
+    /// Compute the wind rose of each calendar month
+    ///
+    /// ## Parameters
+    /// - `sectors`: Number of direction sectors
+    /// - `speed_classes`: Increasing upper bounds of the speed classes in m/s, e.g.
+    ///   [DEFAULT_SPEED_CLASSES]
+    ///
+    /// ## Returns
+    /// The wind rose of each month, January first
+    pub fn monthly_wind_roses(
+        &self,
+        sectors: DirectionSectors,
+        speed_classes: &[f64],
+    ) -> Vec<WindRose> {
+        (1..=12)
+            .map(|month| self.wind_rose_filtered(sectors, speed_classes, |t| t.month() == month))
+            .collect()
+    }
+}
+
+/// Maximum likelihood Weibull parameters of positive wind speeds
+///
+/// The shape parameter `k` solves `Σ vᵏ ln v / Σ vᵏ - 1 / k - mean(ln v) = 0`, whose left side
+/// increases with `k`, by bisection, and the scale parameter is `c = mean(vᵏ)^(1 / k)`.
+fn _fit_weibull(speeds: &[f64]) -> Option<WeibullParameters> {
+    if speeds.len() < 2 {
+        return None;
+    }
+    let count = speeds.len() as f64;
+    let mean_log = speeds.iter().map(|v| v.ln()).sum::<f64>() / count;
+    let likelihood_slope = |k: f64| {
+        let weighted: f64 = speeds.iter().map(|v| v.powf(k) * v.ln()).sum();
+        let total: f64 = speeds.iter().map(|v| v.powf(k)).sum();
+        weighted / total - 1. / k - mean_log
+    };
+
+    let mut low = 0.01;
+    let mut high = 50.;
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        if likelihood_slope(mid) > 0. {
+            high = mid;
+        } else {
+            low = mid;
+        }
+        if high - low < 1e-9 {
+            break;
+        }
+    }
+    let shape = 0.5 * (low + high);
+    let scale = (speeds.iter().map(|v| v.powf(shape)).sum::<f64>() / count).powf(1. / shape);
+    Some(WeibullParameters { shape, scale })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPWFile;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_wind_rose() {
+        let epw = EPWFile::from_path("./data/USA_FL_Tampa_TMY2.epw").unwrap();
+        let data = &epw.data;
+        let rose = data.wind_rose(DirectionSectors::Sixteen, &DEFAULT_SPEED_CLASSES);
+        assert_eq!(16, rose.frequencies.len());
+        assert_eq!(6, rose.frequencies[0].len());
+        assert_eq!(8760, rose.record_count);
+        let total: f64 = rose.sector_frequencies().iter().sum::<f64>() + rose.calm_fraction;
+        assert!((1. - total).abs() < 1e-9);
+        assert!(rose.calm_fraction > 0. && rose.calm_fraction < 0.5);
+        let mean: f64 = data.wind_speed.iter().sum::<f64>() / 8760.;
+        assert!((mean - rose.mean_speed).abs() < 1e-9);
+
+        let sector_frequencies = rose.sector_frequencies();
+        let prevailing = (rose.prevailing_direction / 22.5) as usize;
+        assert!(sector_frequencies
+            .iter()
+            .all(|f| *f <= sector_frequencies[prevailing]));
+        assert_eq!(337.5, rose.sector_direction(15));
+
+        // The Weibull mean c Γ(1 + 1/k) is close to the mean of the speeds that are not calm
+        let weibull = rose.weibull.unwrap();
+        assert!(weibull.shape > 1. && weibull.shape < 4.);
+        let speeds: Vec<f64> = data
+            .wind_speed
+            .iter()
+            .copied()
+            .filter(|v| *v >= CALM_WIND_SPEED)
+            .collect();
+        let mean = speeds.iter().sum::<f64>() / speeds.len() as f64;
+        assert!((weibull.scale * 0.89 - mean).abs() < 0.1 * mean);
+
+        let monthly = data.monthly_wind_roses(DirectionSectors::Eight, &[5.]);
+        assert_eq!(12, monthly.len());
+        assert_eq!(744, monthly[0].record_count);
+        assert_eq!(2, monthly[0].frequencies[0].len());
+
+        let daytime = data.wind_rose_filtered(DirectionSectors::ThirtySix, &[], |t| {
+            (8..18).contains(&t.hour())
+        });
+        assert_eq!(3650, daytime.record_count);
+    }
+}